@@ -25,6 +25,11 @@ fn run() -> fejixwm::errors::Result<()> {
         &client,
         &WindowInfo {
             size: PixelSize::new(800, 600),
+            position: None,
+            is_transparent: false,
+            parent_id: None,
+            size_constraints: SizeConstraints::default(),
+            is_resizable: true,
         },
         &()
     )?;
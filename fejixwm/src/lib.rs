@@ -7,6 +7,8 @@ pub use fejixwm_core::{
     errors,
     events,
     interface,
+    keyboard,
+    mouse,
 };
 
 #[cfg(not(feature = "_platform_selected"))]
@@ -24,5 +26,7 @@ pub use platform::*;
 pub mod prelude {
     pub use core::*;
     pub use events::*;
+    pub use keyboard::*;
+    pub use mouse::*;
     pub use platform::*;
 }
\ No newline at end of file
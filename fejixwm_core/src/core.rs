@@ -25,6 +25,50 @@ pub struct PixelSize {
 }
 
 
+/// Coordinates relative to the top-left corner of a window or a screen
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PixelPosition {
+    pub x: i32,
+    pub y: i32,
+}
+
+
+/// A rectangle relative to the top-left corner of a window or a screen
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PixelRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+
+/// Ratio of the width to the height, e.g. 16:9
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AspectRatio {
+    pub width: u32,
+    pub height: u32,
+}
+
+
+/// Limits the sizes the user can resize the window to. `None` means no limit.
+/// The shell may ignore some of the constraints.
+#[derive(Clone, Default)]
+pub struct SizeConstraints {
+    pub min_size: Option<PixelSize>,
+    pub max_size: Option<PixelSize>,
+
+    /// The window is resized in steps of this size (e.g. terminal character cells)
+    pub size_increment: Option<PixelSize>,
+
+    /// The size the increments are counted from. Defaults to the minimum size.
+    pub base_size: Option<PixelSize>,
+
+    /// The minimum and the maximum aspect ratio of the content
+    pub aspect_ratio: Option<(AspectRatio, AspectRatio)>,
+}
+
+
 #[derive(Clone, Copy, PartialEq)]
 pub enum ShellSubsystem {
     MouseInput,
@@ -45,10 +89,31 @@ pub struct ShellClientInfo<'a> {
 }
 
 
-// TODO Window class (default/tooltip/splash/menu/etc.), flags, parent, etc.
+// TODO Window class (default/tooltip/splash/menu/etc.), flags, etc.
 #[derive(Clone)]
 pub struct WindowInfo {
     pub size: PixelSize,
+
+    /// Position of the content, see [crate::interface::window_manip::MoveController].
+    /// The shell chooses the position if `None`.
+    pub position: Option<PixelPosition>,
+
+    /// Makes the window content blend with the windows behind it according to the alpha channel.
+    /// Colors of the content must be premultiplied by alpha.
+    ///
+    /// Requires a compositing shell. Window creation fails if the shell has no visual with an alpha channel.
+    pub is_transparent: bool,
+
+    /// Creates the window inside another window, which may belong to another process (e.g. a plugin host).
+    /// The identifier is native to the platform, see [WindowTrait::get_id].
+    ///
+    /// Child windows are not managed by the shell and have no decorations.
+    pub parent_id: Option<WindowId>,
+
+    pub size_constraints: SizeConstraints,
+
+    /// Non-resizable windows keep their size unless it is changed by the program
+    pub is_resizable: bool,
 }
 
 
@@ -59,6 +124,15 @@ pub trait WindowTrait : Sized {
     /// Returns the cached size. The cached size is updated by [ShellClientTrait::process_message].
     fn get_size(&self) -> PixelSize;
 
+    /// Returns the cached position of the window's content relative to the screen,
+    /// or to the parent for windows created with [WindowInfo::parent_id].
+    /// The cached position is updated by [ShellClientTrait::process_message].
+    fn get_position(&self) -> PixelPosition;
+
+    /// Returns the cached size including the decorations drawn by the shell (if known).
+    /// The cached size is updated by [ShellClientTrait::process_message].
+    fn get_outer_size(&self) -> PixelSize;
+
 }
 
 
@@ -153,6 +227,23 @@ pub trait ShellClientTrait : Sized {
     }
 
 
+    /// Schedules a [crate::events::WindowEvent::Redraw] of the whole window.
+    ///
+    /// Multiple requests made during one iteration of [ShellClientTrait::listen_to_messages] are merged,
+    /// and the event is delivered at the end of the iteration.
+    fn request_redraw(&self, window: &Self::Window)
+        -> Result<()>;
+
+
+    /// Tells the shell that a frame has been presented to the window.
+    ///
+    /// Canvases of FejixWM call it themselves. Renderers that present frames on their own (e.g. through a Vulkan
+    /// surface or a raw window handle) must call it after every present. Otherwise a shell that synchronizes
+    /// with the application (see [ShellSubsystem::SysRedraw]) waits for the frame forever.
+    fn notify_frame_presented(&self, window: &mut Self::Window)
+        -> Result<()>;
+
+
     /// Asks the shell for the current window size.
     fn get_window_size(&self, window: &Self::Window)
         -> Result<PixelSize>;
@@ -196,6 +287,47 @@ impl std::fmt::Display for PixelSize {
 }
 
 
+impl AspectRatio {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+}
+
+
+impl PixelPosition {
+    pub fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+}
+
+impl std::fmt::Display for PixelPosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({},{})", self.x, self.y)
+    }
+}
+
+
+impl PixelRect {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    pub fn get_position(&self) -> PixelPosition {
+        PixelPosition::new(self.x, self.y)
+    }
+
+    pub fn get_size(&self) -> PixelSize {
+        PixelSize::new(self.width, self.height)
+    }
+}
+
+impl std::fmt::Display for PixelRect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({},{} {}x{})", self.x, self.y, self.width, self.height)
+    }
+}
+
+
 impl PartialEq for PixelSize {
     fn eq(&self, other: &Self) -> bool {
         self.width == other.width && self.height == other.height
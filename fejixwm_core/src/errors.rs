@@ -23,6 +23,9 @@ pub enum Error {
 
     /// You should specify a certain Cargo feature during compilation
     FeatureNotCompiled,
+
+    /// The pixel format cannot be displayed by the platform
+    PixelFormatNotSupported,
 }
 
 
@@ -36,6 +39,7 @@ impl std::fmt::Display for Error {
             Self::SubsystemNotAvailable => write!(f, "the subsystem is not available"),
             Self::SubsystemForced => write!(f, "the subsystem state is forced"),
             Self::FeatureNotCompiled => write!(f, "feature was not compiled"),
+            Self::PixelFormatNotSupported => write!(f, "the pixel format is not supported"),
         }
     }
 }
\ No newline at end of file
@@ -1,8 +1,12 @@
-use crate::core::*;
+use crate::{
+    core::*,
+    keyboard::*,
+    mouse::*,
+};
 
 use std::{
     any::Any,
-    ops::FnMut,
+    ops::{FnMut, Range},
 };
 
 
@@ -35,6 +39,63 @@ pub enum GlobalEvent {
 pub enum WindowEvent {
     Close,
     Resize { new_size: PixelSize },
+
+    /// `new_position` is the position of the window's content relative to the screen,
+    /// or to the parent for windows created with [crate::core::WindowInfo::parent_id]
+    Move { new_position: PixelPosition },
+
+    /// The window content must be repainted. `damage` lists the areas that need repainting and is never empty.
+    ///
+    /// Generated when the shell exposes parts of the window or after [crate::ShellClientTrait::request_redraw].
+    Redraw { damage: Vec<PixelRect> },
+
+    /// The window has received keyboard focus
+    FocusGained,
+    /// The window has lost keyboard focus
+    FocusLost,
+
+    /// The top-level window that contains this window has been activated or deactivated.
+    /// Sent only to windows embedded into other applications.
+    ActivationChange { is_active: bool },
+
+    /// The application that embeds this window has shown or hidden a modal dialog.
+    /// The window should not react to user input while `is_modal` is set.
+    ModalityChange { is_modal: bool },
+
+    /// `is_repeat` is true if the key is being held down and the press is generated by auto-repeat.
+    KeyPress { code: KeyCode, key: LogicalKey, modifiers: KeyModifiers, is_repeat: bool },
+    KeyRelease { code: KeyCode, key: LogicalKey, modifiers: KeyModifiers },
+
+    /// `position` is relative to the window
+    MouseButtonPress { button: MouseButton, position: PixelPosition, modifiers: KeyModifiers },
+    /// `position` is relative to the window
+    MouseButtonRelease { button: MouseButton, position: PixelPosition, modifiers: KeyModifiers },
+
+    /// `position` is relative to the window
+    PointerMove { position: PixelPosition },
+    /// The pointer has entered the window at the `position` relative to the window
+    PointerEnter { position: PixelPosition },
+    /// The pointer has left the window
+    PointerLeave,
+
+    Scroll { delta: ScrollDelta },
+
+    /// Text committed by the user or the input method. Produced only if [crate::ShellSubsystem::TextInput] is
+    /// enabled for the window.
+    TextInput { text: String },
+
+    /// The input method has started composing text
+    PreeditStart,
+
+    /// The text being composed by the input method has changed. The text is not committed yet and should be
+    /// displayed in place of the text cursor.
+    ///
+    /// `cursor_range` is a byte range of `text` that should be highlighted as the cursor.
+    /// It is empty for a thin cursor and `None` if the cursor should be hidden.
+    PreeditUpdate { text: String, cursor_range: Option<Range<usize>> },
+
+    /// The input method has finished composing text. Committed text is reported with [WindowEvent::TextInput].
+    PreeditEnd,
 }
 
 
@@ -106,6 +167,37 @@ impl std::fmt::Display for WindowEvent {
         match self {
             Self::Close => write!(f, "closed"),
             Self::Resize { new_size } => write!(f, "resized to {new_size}"),
+            Self::Move { new_position } => write!(f, "moved to {new_position}"),
+            Self::Redraw { damage } => {
+                write!(f, "redraw requested for")?;
+                for rect in damage {
+                    write!(f, " {rect}")?;
+                }
+                Ok(())
+            }
+            Self::FocusGained => write!(f, "focus gained"),
+            Self::FocusLost => write!(f, "focus lost"),
+            Self::ActivationChange { is_active } => write!(f, "activation changed (active: {is_active})"),
+            Self::ModalityChange { is_modal } => write!(f, "modality changed (modal: {is_modal})"),
+            Self::KeyPress { code, key, modifiers, is_repeat } => {
+                let repeat = if *is_repeat { " (repeat)" } else { "" };
+                write!(f, "key {code} pressed as {key} with {modifiers}{repeat}")
+            }
+            Self::KeyRelease { code, key, modifiers } =>
+                write!(f, "key {code} released as {key} with {modifiers}"),
+            Self::MouseButtonPress { button, position, modifiers } =>
+                write!(f, "mouse button {button} pressed at {position} with {modifiers}"),
+            Self::MouseButtonRelease { button, position, modifiers } =>
+                write!(f, "mouse button {button} released at {position} with {modifiers}"),
+            Self::PointerMove { position } => write!(f, "pointer moved to {position}"),
+            Self::PointerEnter { position } => write!(f, "pointer entered at {position}"),
+            Self::PointerLeave => write!(f, "pointer left"),
+            Self::Scroll { delta } => write!(f, "scrolled by {delta}"),
+            Self::TextInput { text } => write!(f, "text input {text:?}"),
+            Self::PreeditStart => write!(f, "pre-edit started"),
+            Self::PreeditUpdate { text, cursor_range } =>
+                write!(f, "pre-edit {text:?} with cursor at {cursor_range:?}"),
+            Self::PreeditEnd => write!(f, "pre-edit ended"),
         }
     }
 }
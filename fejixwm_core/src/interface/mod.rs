@@ -1,5 +1,7 @@
 pub mod rawpix;
 pub mod window_manip;
+pub mod text_input;
 pub mod null_canvas;
 // pub mod clipboard;
-// pub mod opengl;
+pub mod opengl;
+pub mod vulkan;
@@ -3,14 +3,49 @@ use crate::{
     *
 };
 
-use libc::c_void;
+use std::ffi::c_void;
+
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OpenglApi {
+    Opengl,
+    OpenglEs,
+}
+
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OpenglSwapInterval {
+    /// Buffers are swapped immediately, which may cause tearing
+    Immediate,
+
+    /// Swaps are synchronized with the vertical blank
+    Vsync,
+
+    /// Swaps are synchronized with the vertical blank unless the frame is late
+    AdaptiveVsync,
+}
 
 
 pub struct OpenglInfo {
+    /// Not every canvas supports both APIs
+    pub api: OpenglApi,
+
     pub major_version: u8,
     pub minor_version: u8,
+
+    /// Ignored by OpenGL ES
     pub compatibility_flag: bool,
 
+    /// Requests a context that reports errors and performance issues via `KHR_debug`
+    pub debug_flag: bool,
+
+    /// Requests robust buffer access and notification of graphics resets.
+    /// After a reset, the context must be recreated.
+    pub robustness_flag: bool,
+
+    /// Requests a framebuffer that converts linear colors to sRGB on write
+    pub srgb_flag: bool,
+
     pub red_bits: u8,
     pub green_bits: u8,
     pub blue_bits: u8,
@@ -27,6 +62,17 @@ pub struct OpenglInfo {
 
 pub trait OpenglCanvasTrait : CanvasTrait {
 
+    /// Like [CanvasTrait::new], but textures, buffers and other objects are shared with the context of
+    /// `shared_canvas`, which must belong to the same client.
+    /// 
+    /// Returns `GraphicsApiFailed` if the contexts are not compatible.
+    fn new_shared(
+        client: &Self::ShellClient,
+        window_info: &WindowInfo,
+        canvas_info: &Self::CanvasInfo,
+        shared_canvas: &Self
+    ) -> Result<(<Self::ShellClient as ShellClientTrait>::Window, Self)>;
+
     fn make_current(
         &self,
         client: &Self::ShellClient,
@@ -40,6 +86,14 @@ pub trait OpenglCanvasTrait : CanvasTrait {
         window: &mut <Self::ShellClient as ShellClientTrait>::Window
     ) -> Result<()>;
 
+    /// Returns `GraphicsApiFailed` if the context is not current or the interval is not supported
+    fn set_swap_interval(
+        &self,
+        client: &Self::ShellClient,
+        window: &mut <Self::ShellClient as ShellClientTrait>::Window,
+        swap_interval: OpenglSwapInterval
+    ) -> Result<()>;
+
     /// Returns `GraphicsApiFailed` if the context is not current.
     /// Returns `InvalidArgument` if the function does not exist.
     fn load_function(
@@ -0,0 +1,608 @@
+//! Conversion of pixel data between [PixelFormat]s.
+//!
+//! Conversions between 4-byte formats are performed on whole 32-bit pixels with branchless shifts and masks,
+//! which compilers turn into SIMD code.
+
+use super::*;
+
+use crate::errors::Error;
+
+
+/// Borrowed pixel data
+#[derive(Clone, Copy)]
+pub struct PixelView<'a> {
+    pub format: PixelFormat,
+    pub width: u32,
+    pub height: u32,
+
+    /// Number of padding bytes added after each row, see [RawpixData::get_padding]
+    pub padding: usize,
+
+    pub pixels: &'a [u8],
+}
+
+
+/// Mutably borrowed pixel data
+pub struct PixelViewMut<'a> {
+    pub format: PixelFormat,
+    pub width: u32,
+    pub height: u32,
+
+    /// Number of padding bytes added after each row, see [RawpixData::get_padding]
+    pub padding: usize,
+
+    pub pixels: &'a mut [u8],
+}
+
+
+/// Byte offsets of the pixel components
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PixelComponents {
+    pub red: usize,
+    pub green: usize,
+    pub blue: usize,
+    pub alpha: Option<usize>,
+}
+
+
+/// Converts 4-byte pixels stored as little-endian 32-bit words
+#[derive(Clone, Copy)]
+struct PixelSwizzle {
+    /// Source shift, mask and destination shift for every component
+    components: [(u32, u32, u32); 4],
+
+    /// Bits set in every destination pixel (unused bytes and alpha missing in the source)
+    fill: u32,
+}
+
+
+
+impl PixelFormat {
+
+    pub fn get_components(&self) -> PixelComponents {
+        let (red, green, blue, alpha) = match self {
+            Self::RGB_888 => (0, 1, 2, None),
+            Self::BGR_888 => (2, 1, 0, None),
+            Self::RGBX_8888 => (0, 1, 2, None),
+            Self::XRGB_8888 => (1, 2, 3, None),
+            Self::BGRX_8888 => (2, 1, 0, None),
+            Self::XBGR_8888 => (3, 2, 1, None),
+            Self::RGBA_8888 => (0, 1, 2, Some(3)),
+            Self::ARGB_8888 => (1, 2, 3, Some(0)),
+            Self::BGRA_8888 => (2, 1, 0, Some(3)),
+            Self::ABGR_8888 => (3, 2, 1, Some(0)),
+        };
+
+        PixelComponents { red, green, blue, alpha }
+    }
+
+
+    pub fn has_alpha(&self) -> bool {
+        self.get_components().alpha.is_some()
+    }
+
+}
+
+
+/// Returns the number of bytes needed to store the pixels. The padding after the last row is optional.
+fn get_required_length(format: PixelFormat, width: u32, height: u32, padding: usize) -> usize {
+    let row_length = width as usize * format.size_of_pixel();
+
+    match height as usize {
+        0 => 0,
+        height => (height - 1) * (row_length + padding) + row_length,
+    }
+}
+
+
+impl<'a> PixelView<'a> {
+
+    pub fn get_stride(&self) -> usize {
+        self.width as usize * self.format.size_of_pixel() + self.padding
+    }
+
+
+    /// Returns [Error::InvalidArgument] if `pixels` is too short for the size and the padding
+    pub fn validate(&self) -> Result<()> {
+        match self.pixels.len() >= get_required_length(self.format, self.width, self.height, self.padding) {
+            true => Ok(()),
+            false => Err(Error::InvalidArgument),
+        }
+    }
+
+
+    fn get_row(&self, row: usize) -> &'a [u8] {
+        let start = row * self.get_stride();
+        &self.pixels[start .. start + self.width as usize * self.format.size_of_pixel()]
+    }
+
+}
+
+
+impl<'a> PixelViewMut<'a> {
+
+    pub fn get_stride(&self) -> usize {
+        self.width as usize * self.format.size_of_pixel() + self.padding
+    }
+
+
+    /// Returns [Error::InvalidArgument] if `pixels` is too short for the size and the padding
+    pub fn validate(&self) -> Result<()> {
+        self.as_view().validate()
+    }
+
+
+    pub fn as_view(&self) -> PixelView<'_> {
+        PixelView {
+            format: self.format,
+            width: self.width,
+            height: self.height,
+            padding: self.padding,
+            pixels: self.pixels,
+        }
+    }
+
+
+    fn get_row_mut(&mut self, row: usize) -> &mut [u8] {
+        let start = row * self.get_stride();
+        let length = self.width as usize * self.format.size_of_pixel();
+        &mut self.pixels[start .. start + length]
+    }
+
+}
+
+
+impl PixelSwizzle {
+
+    fn new(src_format: PixelFormat, dst_format: PixelFormat) -> Self {
+        let src = src_format.get_components();
+        let dst = dst_format.get_components();
+
+        let component = |src_offset: usize, dst_offset: usize| {
+            ((src_offset * 8) as u32, 0xFF, (dst_offset * 8) as u32)
+        };
+
+        let mut components = [
+            component(src.red, dst.red),
+            component(src.green, dst.green),
+            component(src.blue, dst.blue),
+            (0, 0, 0),
+        ];
+
+        // Every byte that does not receive a component is set to 0xFF, which makes missing alpha opaque
+        let mut fill = !((0xFF << (dst.red * 8)) | (0xFF << (dst.green * 8)) | (0xFF << (dst.blue * 8)));
+
+        if let (Some(src_alpha), Some(dst_alpha)) = (src.alpha, dst.alpha) {
+            components[3] = component(src_alpha, dst_alpha);
+            fill &= !(0xFF << (dst_alpha * 8));
+        }
+
+        Self { components, fill }
+    }
+
+
+    #[inline(always)]
+    fn apply(&self, pixel: u32) -> u32 {
+        let [c0, c1, c2, c3] = self.components;
+
+        self.fill
+            | (((pixel >> c0.0) & c0.1) << c0.2)
+            | (((pixel >> c1.0) & c1.1) << c1.2)
+            | (((pixel >> c2.0) & c2.1) << c2.2)
+            | (((pixel >> c3.0) & c3.1) << c3.2)
+    }
+
+
+    fn convert_row(&self, src: &[u8], dst: &mut [u8]) {
+        for (src_pixel, dst_pixel) in src.chunks_exact(4).zip(dst.chunks_exact_mut(4)) {
+            let pixel = u32::from_le_bytes([src_pixel[0], src_pixel[1], src_pixel[2], src_pixel[3]]);
+            dst_pixel.copy_from_slice(&self.apply(pixel).to_le_bytes());
+        }
+    }
+
+}
+
+
+
+/// Converts `width` pixels. Returns [Error::InvalidArgument] if a row is shorter than `width` pixels.
+///
+/// Alpha is dropped if the destination has no alpha and is opaque if the source has no alpha.
+/// Unused bytes of the destination are set to 0xFF, unless the formats are the same and the pixels are copied.
+pub fn convert_row(src: &[u8], src_format: PixelFormat, dst: &mut [u8], dst_format: PixelFormat, width: usize)
+    -> Result<()>
+{
+    let src = src.get(.. width * src_format.size_of_pixel()).ok_or(Error::InvalidArgument)?;
+    let dst = dst.get_mut(.. width * dst_format.size_of_pixel()).ok_or(Error::InvalidArgument)?;
+
+    if src_format == dst_format {
+        dst.copy_from_slice(src);
+    } else if src_format.size_of_pixel() == 4 && dst_format.size_of_pixel() == 4 {
+        PixelSwizzle::new(src_format, dst_format).convert_row(src, dst);
+    } else {
+        convert_row_bytewise(src, src_format, dst, dst_format);
+    }
+
+    Ok(())
+}
+
+
+fn convert_row_bytewise(src: &[u8], src_format: PixelFormat, dst: &mut [u8], dst_format: PixelFormat) {
+    let src_components = src_format.get_components();
+    let dst_components = dst_format.get_components();
+
+    let src_pixels = src.chunks_exact(src_format.size_of_pixel());
+    let dst_pixels = dst.chunks_exact_mut(dst_format.size_of_pixel());
+
+    for (src_pixel, dst_pixel) in src_pixels.zip(dst_pixels) {
+        dst_pixel.fill(0xFF);
+
+        dst_pixel[dst_components.red] = src_pixel[src_components.red];
+        dst_pixel[dst_components.green] = src_pixel[src_components.green];
+        dst_pixel[dst_components.blue] = src_pixel[src_components.blue];
+
+        if let (Some(src_alpha), Some(dst_alpha)) = (src_components.alpha, dst_components.alpha) {
+            dst_pixel[dst_alpha] = src_pixel[src_alpha];
+        }
+    }
+}
+
+
+/// Copies the `src_rect` of the source to the `dst_position` of the destination, converting the pixels.
+///
+/// The copied area is clipped to the bounds of both images.
+/// Returns [Error::InvalidArgument] if the pixels of an image are too short for its size.
+pub fn blit(src: &PixelView, src_rect: PixelRect, dst: &mut PixelViewMut, dst_position: PixelPosition)
+    -> Result<()>
+{
+    src.validate()?;
+    dst.validate()?;
+
+    // Clip the source rectangle to the source image
+    let left = src_rect.x.max(0) as i64;
+    let top = src_rect.y.max(0) as i64;
+    let right = (src_rect.x as i64 + src_rect.width as i64).min(src.width as i64);
+    let bottom = (src_rect.y as i64 + src_rect.height as i64).min(src.height as i64);
+
+    // Shift the destination position accordingly and clip to the destination image
+    let dst_left = dst_position.x as i64 + (left - src_rect.x as i64);
+    let dst_top = dst_position.y as i64 + (top - src_rect.y as i64);
+
+    let skip_x = (-dst_left).max(0);
+    let skip_y = (-dst_top).max(0);
+
+    let width = (right - left - skip_x).min(dst.width as i64 - dst_left - skip_x);
+    let height = (bottom - top - skip_y).min(dst.height as i64 - dst_top - skip_y);
+
+    if width <= 0 || height <= 0 {
+        return Ok(());
+    }
+
+    let (src_x, src_y) = ((left + skip_x) as usize, (top + skip_y) as usize);
+    let (dst_x, dst_y) = ((dst_left + skip_x) as usize, (dst_top + skip_y) as usize);
+    let width = width as usize;
+
+    let src_pixel_size = src.format.size_of_pixel();
+    let dst_pixel_size = dst.format.size_of_pixel();
+    let dst_format = dst.format;
+
+    for row in 0..height as usize {
+        let src_row = &src.get_row(src_y + row)[src_x * src_pixel_size ..];
+        let dst_row = &mut dst.get_row_mut(dst_y + row)[dst_x * dst_pixel_size ..];
+        convert_row(src_row, src.format, dst_row, dst_format, width)?;
+    }
+
+    Ok(())
+}
+
+
+/// Converts the whole image. Both images must have the same size.
+pub fn convert(src: &PixelView, dst: &mut PixelViewMut) -> Result<()> {
+    if src.width != dst.width || src.height != dst.height {
+        return Err(Error::InvalidArgument);
+    }
+
+    blit(src, PixelRect::new(0, 0, src.width, src.height), dst, PixelPosition::new(0, 0))
+}
+
+
+/// Multiplies color components by alpha. Does nothing if the format has no alpha.
+pub fn premultiply_alpha(image: &mut PixelViewMut) -> Result<()> {
+    let components = image.format.get_components();
+
+    let Some(alpha) = components.alpha else {
+        return Ok(());
+    };
+
+    for_each_pixel(image, |pixel| {
+        let a = pixel[alpha] as u32;
+
+        for c in [components.red, components.green, components.blue] {
+            pixel[c] = ((pixel[c] as u32 * a + 127) / 255) as u8;
+        }
+    })
+}
+
+
+/// Divides color components by alpha. Fully transparent pixels become black.
+/// Does nothing if the format has no alpha.
+pub fn unpremultiply_alpha(image: &mut PixelViewMut) -> Result<()> {
+    let components = image.format.get_components();
+
+    let Some(alpha) = components.alpha else {
+        return Ok(());
+    };
+
+    for_each_pixel(image, |pixel| {
+        let a = pixel[alpha] as u32;
+
+        for c in [components.red, components.green, components.blue] {
+            pixel[c] = (pixel[c] as u32 * 255 + a / 2).checked_div(a).unwrap_or(0).min(255) as u8;
+        }
+    })
+}
+
+
+fn for_each_pixel(image: &mut PixelViewMut, mut function: impl FnMut(&mut [u8])) -> Result<()> {
+    image.validate()?;
+
+    let pixel_size = image.format.size_of_pixel();
+
+    for row in 0..image.height as usize {
+        for pixel in image.get_row_mut(row).chunks_exact_mut(pixel_size) {
+            function(pixel);
+        }
+    }
+
+    Ok(())
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_FORMATS: [PixelFormat; 10] = [
+        PixelFormat::RGB_888,
+        PixelFormat::BGR_888,
+        PixelFormat::RGBX_8888,
+        PixelFormat::XRGB_8888,
+        PixelFormat::BGRX_8888,
+        PixelFormat::XBGR_8888,
+        PixelFormat::RGBA_8888,
+        PixelFormat::ARGB_8888,
+        PixelFormat::BGRA_8888,
+        PixelFormat::ABGR_8888,
+    ];
+
+    const RED: u8 = 0x11;
+    const GREEN: u8 = 0x22;
+    const BLUE: u8 = 0x33;
+    const ALPHA: u8 = 0x44;
+
+    /// Unused bytes of the source are set to garbage
+    fn make_pixel(format: PixelFormat) -> Vec<u8> {
+        let components = format.get_components();
+        let mut pixel = vec![0x99; format.size_of_pixel()];
+
+        pixel[components.red] = RED;
+        pixel[components.green] = GREEN;
+        pixel[components.blue] = BLUE;
+
+        if let Some(alpha) = components.alpha {
+            pixel[alpha] = ALPHA;
+        }
+
+        pixel
+    }
+
+    fn check_conversion(src_format: PixelFormat, dst_format: PixelFormat) {
+        let src = make_pixel(src_format);
+        let mut dst = vec![0; dst_format.size_of_pixel()];
+
+        convert_row(&src, src_format, &mut dst, dst_format, 1).unwrap();
+
+        // Pixels of the same format are copied as they are
+        if src_format == dst_format {
+            assert_eq!(dst, src, "{src_format:?}");
+            return;
+        }
+
+        let components = dst_format.get_components();
+        let mut expected = vec![0xFF; dst_format.size_of_pixel()];
+
+        expected[components.red] = RED;
+        expected[components.green] = GREEN;
+        expected[components.blue] = BLUE;
+
+        if let (Some(alpha), true) = (components.alpha, src_format.has_alpha()) {
+            expected[alpha] = ALPHA;
+        }
+
+        assert_eq!(dst, expected, "{src_format:?} -> {dst_format:?}");
+    }
+
+    /// Red is the x coordinate, green is the y coordinate
+    fn make_gradient(width: u32, height: u32) -> Vec<u8> {
+        (0..height).flat_map(|y| (0..width).flat_map(move |x| [x as u8, y as u8, 1, 0xFF])).collect()
+    }
+
+    fn view(pixels: &[u8], width: u32, height: u32) -> PixelView<'_> {
+        PixelView { format: PixelFormat::RGBA_8888, width, height, padding: 0, pixels }
+    }
+
+
+    #[test]
+    fn convert_row_between_4_byte_formats() {
+        for src_format in ALL_FORMATS.into_iter().filter(|format| format.size_of_pixel() == 4) {
+            for dst_format in ALL_FORMATS.into_iter().filter(|format| format.size_of_pixel() == 4) {
+                check_conversion(src_format, dst_format);
+            }
+        }
+    }
+
+
+    #[test]
+    fn convert_row_between_3_and_4_byte_formats() {
+        for src_format in ALL_FORMATS {
+            for dst_format in ALL_FORMATS {
+                if src_format.size_of_pixel() == 3 || dst_format.size_of_pixel() == 3 {
+                    check_conversion(src_format, dst_format);
+                }
+            }
+        }
+    }
+
+
+    #[test]
+    fn convert_row_fills_missing_alpha() {
+        let mut dst = [0; 4];
+        convert_row(&[RED, GREEN, BLUE], PixelFormat::RGB_888, &mut dst, PixelFormat::ARGB_8888, 1).unwrap();
+        assert_eq!(dst, [0xFF, RED, GREEN, BLUE]);
+    }
+
+
+    #[test]
+    fn convert_row_rejects_short_rows() {
+        let mut dst = [0; 8];
+
+        let result = convert_row(&[0; 7], PixelFormat::RGBA_8888, &mut dst, PixelFormat::BGRA_8888, 2);
+        assert!(matches!(result, Err(Error::InvalidArgument)));
+
+        let result = convert_row(&[0; 8], PixelFormat::RGBA_8888, &mut dst[..7], PixelFormat::BGRA_8888, 2);
+        assert!(matches!(result, Err(Error::InvalidArgument)));
+    }
+
+
+    #[test]
+    fn blit_clips_negative_source_rect() {
+        let src = make_gradient(4, 4);
+        let mut pixels = vec![0; 3 * 16 + 4 * 2];
+
+        let mut dst = PixelViewMut {
+            format: PixelFormat::RGBA_8888, width: 3, height: 3, padding: 4, pixels: &mut pixels,
+        };
+
+        blit(&view(&src, 4, 4), PixelRect::new(-1, -1, 3, 3), &mut dst, PixelPosition::new(1, 1)).unwrap();
+
+        // Only the source pixel (0, 0) lands on the destination pixel (2, 2)
+        let mut expected = vec![0; pixels.len()];
+        expected[2 * 16 + 2 * 4 .. 2 * 16 + 3 * 4].copy_from_slice(&[0, 0, 1, 0xFF]);
+
+        assert_eq!(pixels, expected);
+    }
+
+
+    #[test]
+    fn blit_clips_negative_destination_position() {
+        let src = make_gradient(4, 4);
+        let mut pixels = vec![0; 3 * 3 * 4];
+
+        let mut dst = PixelViewMut {
+            format: PixelFormat::RGBA_8888, width: 3, height: 3, padding: 0, pixels: &mut pixels,
+        };
+
+        blit(&view(&src, 4, 4), PixelRect::new(0, 0, 4, 4), &mut dst, PixelPosition::new(-2, -1)).unwrap();
+
+        for y in 0..3 {
+            for x in 0..3 {
+                let pixel = &pixels[(y * 3 + x) * 4 ..][..4];
+
+                if x < 2 {
+                    assert_eq!(pixel, [x as u8 + 2, y as u8 + 1, 1, 0xFF], "pixel ({x},{y})");
+                } else {
+                    assert_eq!(pixel, [0; 4], "pixel ({x},{y})");
+                }
+            }
+        }
+    }
+
+
+    #[test]
+    fn blit_ignores_rects_out_of_bounds() {
+        let src = make_gradient(4, 4);
+        let mut pixels = vec![0; 4 * 4 * 4];
+
+        let mut dst = PixelViewMut {
+            format: PixelFormat::RGBA_8888, width: 4, height: 4, padding: 0, pixels: &mut pixels,
+        };
+
+        blit(&view(&src, 4, 4), PixelRect::new(10, 10, 2, 2), &mut dst, PixelPosition::new(0, 0)).unwrap();
+        blit(&view(&src, 4, 4), PixelRect::new(0, 0, 4, 4), &mut dst, PixelPosition::new(4, -4)).unwrap();
+
+        assert!(pixels.iter().all(|byte| *byte == 0));
+    }
+
+
+    #[test]
+    fn convert_rejects_short_buffers() {
+        let src = make_gradient(4, 4);
+        let mut pixels = vec![0; 4 * 4 * 4 - 1];
+
+        let mut dst = PixelViewMut {
+            format: PixelFormat::RGBA_8888, width: 4, height: 4, padding: 0, pixels: &mut pixels,
+        };
+
+        assert!(matches!(convert(&view(&src, 4, 4), &mut dst), Err(Error::InvalidArgument)));
+        assert!(matches!(convert(&view(&src[1..], 4, 4), &mut dst), Err(Error::InvalidArgument)));
+    }
+
+
+    #[test]
+    fn padding_after_last_row_is_optional() {
+        let src = make_gradient(2, 2);
+        let mut pixels = vec![0; 2 * 8 + 3];
+
+        let mut dst = PixelViewMut {
+            format: PixelFormat::RGBA_8888, width: 2, height: 2, padding: 3, pixels: &mut pixels,
+        };
+
+        convert(&view(&src, 2, 2), &mut dst).unwrap();
+        assert_eq!(&pixels[8 + 3 ..], &src[8..]);
+    }
+
+
+    #[test]
+    fn premultiply_and_unpremultiply_round_trip() {
+        for alpha in 0..=255u8 {
+            let mut pixels: Vec<u8> = (0..=255u8).flat_map(|color| [color, color, color, alpha]).collect();
+
+            let mut image = PixelViewMut {
+                format: PixelFormat::RGBA_8888, width: 256, height: 1, padding: 0, pixels: &mut pixels,
+            };
+
+            premultiply_alpha(&mut image).unwrap();
+            unpremultiply_alpha(&mut image).unwrap();
+
+            // Premultiplication loses precision, the lower the alpha, the more
+            let tolerance = match alpha {
+                0 => 255,
+                _ => 255 / (2 * alpha as i32) + 1,
+            };
+
+            for (color, pixel) in pixels.chunks_exact(4).enumerate() {
+                assert_eq!(pixel[3], alpha);
+
+                if alpha == 0 {
+                    assert_eq!(&pixel[..3], [0, 0, 0]);
+                } else {
+                    assert!((pixel[0] as i32 - color as i32).abs() <= tolerance, "color {color}, alpha {alpha}");
+                }
+            }
+        }
+    }
+
+
+    #[test]
+    fn premultiply_ignores_formats_without_alpha() {
+        let mut pixels = vec![0x80; 4];
+
+        let mut image = PixelViewMut {
+            format: PixelFormat::RGBX_8888, width: 1, height: 1, padding: 0, pixels: &mut pixels,
+        };
+
+        premultiply_alpha(&mut image).unwrap();
+        assert_eq!(pixels, [0x80; 4]);
+    }
+
+}
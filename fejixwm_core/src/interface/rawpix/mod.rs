@@ -1,19 +1,18 @@
 #![allow(non_camel_case_types)]
 
+pub mod conversion;
+
 use crate::{
     errors::Result,
     *
 };
 
-use std::{
-    rc::Rc,
-    cell::RefCell,
-};
+use std::ops::DerefMut;
 
 
 /// All components are specified in little-endian order.
 /// Not all formats may be supported (typically, only a few are implemented by a platform).
-#[derive(Clone)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum PixelFormat {
     /// Red 8 bits, Green 8 bits, Blue 8 bits (packed)
     RGB_888,
@@ -41,35 +40,87 @@ pub enum PixelFormat {
 #[derive(Clone)]
 pub struct RawpixInfo {
     
-    format: PixelFormat
+    /// Must be one of [RawpixCanvasTrait::supported_formats].
+    /// `None` selects the format native to the platform.
+    pub format: Option<PixelFormat>
 
 }
 
 
-/// Access to the pixel data is thread-unsafe
-#[derive(Clone)]
+/// Memory that contains pixel data.
+/// 
+/// Platforms may allocate it outside of the Rust heap, e.g. in memory shared with the shell.
+pub type PixelMemory = Box<dyn DerefMut<Target = [u8]> + Send>;
+
+
+/// A back buffer acquired from a canvas.
+/// 
+/// The buffer can be sent to other threads for rendering and must be handed back to the canvas that has
+/// created it with [RawpixCanvasTrait::present] or [RawpixCanvasTrait::release_buffer].
+/// The fields cannot be modified, so the canvas can rely on the buffer it has handed out.
 pub struct RawpixData {
 
-    pub format: PixelFormat,
+    format: PixelFormat,
+
+    size: PixelSize,
 
     /// Number of padding bytes added after each row.
-    pub padding: usize,
+    padding: usize,
+
+    /// Number of frames presented since the contents of this buffer were presented.
+    age: u32,
 
-    /// Pixel data. Contains `height * (width * format.size_of_pixel() + padding)` bytes.
-    pub pixels: Rc<RefCell<Box<[u8]>>>,
+    /// Pixel data. Contains at least `height * (width * format.size_of_pixel() + padding)` bytes.
+    pixels: PixelMemory,
+
+    /// Identifies the buffer within the canvas
+    id: usize,
 
 }
 
 
 pub trait RawpixCanvasTrait : CanvasTrait {
 
-    fn get_raw_pixel_data(&self) -> RawpixData;
+    /// Returns the formats that can be displayed without conversion, the native format goes first.
+    /// 
+    /// Creating a canvas with another format fails with [crate::errors::Error::PixelFormatNotSupported].
+    fn supported_formats(client: &Self::ShellClient) -> Vec<PixelFormat>;
+
+    /// Takes an unused back buffer. A new buffer is allocated if all buffers are acquired, so acquiring two or
+    /// three buffers at once results in double or triple buffering.
+    /// 
+    /// The buffer has the current window size. Buffers acquired before [crate::events::WindowEvent::Resize]
+    /// can still be presented, but they are clipped to the new size.
+    fn acquire_buffer(
+        &mut self,
+        client: &Self::ShellClient,
+        window: &Self::Window
+    ) -> Result<RawpixData>;
 
-    /// Copies the back buffer to the front buffer
+    /// Returns the buffer to the canvas without presenting it
+    fn release_buffer(
+        &mut self,
+        client: &Self::ShellClient,
+        buffer: RawpixData
+    ) -> Result<()>;
+
+    /// Copies the buffer to the window and returns it to the canvas
     fn present(
-        &self,
+        &mut self,
         client: &Self::ShellClient,
-        window: &mut Self::Window
+        window: &mut Self::Window,
+        buffer: RawpixData
+    ) -> Result<()>;
+
+    /// Copies only the specified areas of the buffer to the window and returns the buffer to the canvas.
+    /// 
+    /// The rectangles are clipped to the buffer bounds.
+    fn present_region(
+        &mut self,
+        client: &Self::ShellClient,
+        window: &mut Self::Window,
+        buffer: RawpixData,
+        rects: &[PixelRect]
     ) -> Result<()>;
 
 }
@@ -82,4 +133,58 @@ impl PixelFormat {
             _ => 4
         }
     }
+}
+
+
+impl RawpixData {
+    /// Used by platform implementations. `id` identifies the buffer within the canvas that creates it.
+    pub fn new(id: usize, format: PixelFormat, size: PixelSize, padding: usize, age: u32, pixels: PixelMemory)
+        -> Self
+    {
+        Self { format, size, padding, age, pixels, id }
+    }
+
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+
+    pub fn get_format(&self) -> PixelFormat {
+        self.format
+    }
+
+    pub fn get_size(&self) -> PixelSize {
+        self.size.clone()
+    }
+
+    /// Returns the number of padding bytes added after each row
+    pub fn get_padding(&self) -> usize {
+        self.padding
+    }
+
+    /// Returns the number of frames presented since the contents of this buffer were presented.
+    /// 
+    /// 0 means that the contents are undefined. 1 means that the buffer contains the last presented frame,
+    /// so only the areas changed since then have to be redrawn.
+    pub fn get_age(&self) -> u32 {
+        self.age
+    }
+
+    /// Contains at least `height * stride` bytes, see [Self::get_stride]
+    pub fn get_pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn get_pixels_mut(&mut self) -> &mut [u8] {
+        &mut self.pixels
+    }
+
+    /// Used by platform implementations to take the memory back
+    pub fn into_pixels(self) -> PixelMemory {
+        self.pixels
+    }
+
+    /// Returns the number of bytes between the beginnings of two adjacent rows
+    pub fn get_stride(&self) -> usize {
+        self.size.width as usize * self.format.size_of_pixel() + self.padding
+    }
 }
\ No newline at end of file
@@ -0,0 +1,15 @@
+use crate::{
+    errors::Result,
+    *,
+};
+
+
+pub trait TextInputController : ShellClientTrait {
+
+    /// Tells the input method where the text cursor is (relative to the window), so that the input method can
+    /// display its windows (e.g. the candidate list) next to it.
+    ///
+    /// Has no effect if [ShellSubsystem::TextInput] is disabled for the window.
+    fn set_text_cursor_area(&self, window: &mut Self::Window, area: PixelRect) -> Result<()>;
+
+}
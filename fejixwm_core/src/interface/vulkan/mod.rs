@@ -0,0 +1,56 @@
+use crate::{
+    errors::Result,
+    *
+};
+
+use std::{
+    ffi::{c_void, CStr},
+    os::raw::c_char,
+};
+
+
+/// `VkInstance`
+pub type VulkanInstance = *mut c_void;
+
+/// `VkSurfaceKHR`
+pub type VulkanSurface = u64;
+
+/// `VkAllocationCallbacks*`, may be null
+pub type VulkanAllocationCallbacks = *const c_void;
+
+/// `PFN_vkVoidFunction`
+pub type VulkanVoidFunction = unsafe extern "system" fn();
+
+/// `PFN_vkGetInstanceProcAddr` provided by the Vulkan loader
+pub type VulkanGetInstanceProcAddr = unsafe extern "system" fn(VulkanInstance, *const c_char)
+    -> Option<VulkanVoidFunction>;
+
+
+/// Creates a window whose contents are presented through a Vulkan surface.
+/// 
+/// The library does not link to Vulkan, the functions are loaded through the `vkGetInstanceProcAddr` of the
+/// application.
+pub trait VulkanCanvasTrait : CanvasTrait {
+
+    /// Returns the instance extensions that [VulkanCanvasTrait::create_surface] requires
+    fn required_instance_extensions(client: &Self::ShellClient) -> Vec<&'static CStr>;
+
+    /// Creates a surface for the window.
+    /// 
+    /// The surface is owned by the application and must be destroyed with `vkDestroySurfaceKHR` before the canvas.
+    /// Every `vkQueuePresentKHR` must be followed by [crate::ShellClientTrait::notify_frame_presented].
+    /// 
+    /// # Safety
+    /// 
+    /// `instance` must be a valid instance created with [VulkanCanvasTrait::required_instance_extensions] enabled
+    /// and `get_instance_proc_addr` must belong to its loader.
+    unsafe fn create_surface(
+        &self,
+        client: &Self::ShellClient,
+        window: &<Self::ShellClient as ShellClientTrait>::Window,
+        instance: VulkanInstance,
+        get_instance_proc_addr: VulkanGetInstanceProcAddr,
+        allocator: VulkanAllocationCallbacks
+    ) -> Result<VulkanSurface>;
+
+}
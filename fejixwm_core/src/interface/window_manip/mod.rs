@@ -15,4 +15,45 @@ pub trait VisibilityController : ShellClientTrait {
 
     fn set_visible(&self, window: &mut Self::Window, visible: bool) -> Result<()>;
 
-}
\ No newline at end of file
+}
+
+
+pub trait FocusController : ShellClientTrait {
+
+    /// Asks the shell to give keyboard focus to the window.
+    /// The shell may refuse the request (e.g. to prevent focus stealing).
+    /// If the focus changes, [crate::events::WindowEvent::FocusGained] is sent.
+    fn request_focus(&self, window: &mut Self::Window) -> Result<()>;
+
+}
+
+
+pub trait SizeConstraintsController : ShellClientTrait {
+
+    /// Replaces all constraints previously set for the window
+    fn set_size_constraints(&self, window: &mut Self::Window, constraints: &SizeConstraints) -> Result<()>;
+
+    /// Non-resizable windows keep their current size, the size constraints take effect again once
+    /// the window becomes resizable.
+    fn set_resizable(&self, window: &mut Self::Window, is_resizable: bool) -> Result<()>;
+
+}
+
+
+pub trait ResizeController : ShellClientTrait {
+
+    /// Asks the shell to resize the content of the window. The shell may adjust the size (e.g. to fit the size
+    /// constraints) or refuse the request. If the size changes, [crate::events::WindowEvent::Resize] is sent.
+    fn set_size(&self, window: &mut Self::Window, size: PixelSize) -> Result<()>;
+
+}
+
+
+pub trait MoveController : ShellClientTrait {
+
+    /// Asks the shell to move the content of the window to the position relative to the screen
+    /// (or to the parent for child windows). Called before the window is shown, places the window initially.
+    /// If the position changes, [crate::events::WindowEvent::Move] is sent.
+    fn set_position(&self, window: &mut Self::Window, position: PixelPosition) -> Result<()>;
+
+}
@@ -0,0 +1,146 @@
+/// Physical key position, independent of the keyboard layout.
+///
+/// Variants are named after the keys of a US QWERTY keyboard in the corresponding positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyCode {
+    Escape,
+    Digit1, Digit2, Digit3, Digit4, Digit5, Digit6, Digit7, Digit8, Digit9, Digit0,
+    Minus,
+    Equal,
+    Backspace,
+    Tab,
+    KeyQ, KeyW, KeyE, KeyR, KeyT, KeyY, KeyU, KeyI, KeyO, KeyP,
+    BracketLeft,
+    BracketRight,
+    Enter,
+    ControlLeft,
+    KeyA, KeyS, KeyD, KeyF, KeyG, KeyH, KeyJ, KeyK, KeyL,
+    Semicolon,
+    Quote,
+    Backquote,
+    ShiftLeft,
+    Backslash,
+    KeyZ, KeyX, KeyC, KeyV, KeyB, KeyN, KeyM,
+    Comma,
+    Period,
+    Slash,
+    ShiftRight,
+    AltLeft,
+    Space,
+    CapsLock,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+    F13, F14, F15, F16, F17, F18, F19, F20, F21, F22, F23, F24,
+    NumLock,
+    ScrollLock,
+    Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
+    NumpadAdd,
+    NumpadSubtract,
+    NumpadMultiply,
+    NumpadDivide,
+    NumpadDecimal,
+    NumpadComma,
+    NumpadEqual,
+    NumpadEnter,
+    /// The additional key between the left Shift and Z found on ISO keyboards
+    IntlBackslash,
+    ControlRight,
+    AltRight,
+    PrintScreen,
+    Pause,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Insert,
+    Delete,
+    ArrowUp,
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+    MetaLeft,
+    MetaRight,
+    ContextMenu,
+    AudioVolumeMute,
+    AudioVolumeDown,
+    AudioVolumeUp,
+
+    /// The key is not known to FejixWM. Contains the platform-specific key code (e.g. the X11 keycode).
+    Unknown(u32),
+}
+
+
+/// The meaning of a key according to the current keyboard layout and modifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogicalKey {
+    /// The key produces a character
+    Character(char),
+
+    /// The key does not produce characters (e.g. arrows, function keys, modifiers).
+    /// Such keys are identified by the key code they have on a US QWERTY keyboard.
+    Named(KeyCode),
+
+    /// The key has no known meaning in the current layout
+    Unidentified,
+}
+
+
+/// State of the modifier keys at the moment of an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct KeyModifiers {
+    pub shift: bool,
+    pub control: bool,
+    pub alt: bool,
+    /// The "Windows", "Command" or "Super" key
+    pub meta: bool,
+    pub caps_lock: bool,
+    pub num_lock: bool,
+}
+
+
+
+impl std::fmt::Display for KeyCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unknown(code) => write!(f, "unknown key (code {code})"),
+            _ => write!(f, "{self:?}"),
+        }
+    }
+}
+
+
+impl std::fmt::Display for LogicalKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Character(character) => write!(f, "{character:?}"),
+            Self::Named(code) => write!(f, "{code}"),
+            Self::Unidentified => write!(f, "unidentified"),
+        }
+    }
+}
+
+
+impl std::fmt::Display for KeyModifiers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = [
+            (self.shift, "Shift"),
+            (self.control, "Control"),
+            (self.alt, "Alt"),
+            (self.meta, "Meta"),
+            (self.caps_lock, "CapsLock"),
+            (self.num_lock, "NumLock"),
+        ];
+
+        let mut is_first = true;
+        write!(f, "[")?;
+
+        for (_, name) in names.iter().filter(|(is_active, _)| *is_active) {
+            if !is_first {
+                write!(f, "+")?;
+            }
+            write!(f, "{name}")?;
+            is_first = false;
+        }
+
+        write!(f, "]")
+    }
+}
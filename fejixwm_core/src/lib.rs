@@ -42,5 +42,7 @@ mod core;
 pub mod errors;
 pub mod events;
 pub mod interface;
+pub mod keyboard;
+pub mod mouse;
 
 pub use self::core::*;
@@ -0,0 +1,60 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+
+    /// The "browser back" side button
+    Back,
+
+    /// The "browser forward" side button
+    Forward,
+
+    /// Contains the platform-specific button number
+    Other(u32),
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollUnit {
+    /// One line corresponds to one step of a typical mouse wheel.
+    /// X11 does not tell precise devices (e.g. touchpads) apart, so their movement is measured in lines too.
+    Lines,
+}
+
+
+/// Positive values mean scrolling right (horizontal axis) or down (vertical axis).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollDelta {
+    /// Whole wheel steps.
+    /// Fractional movement of smooth scrolling devices is accumulated until it reaches a whole step.
+    pub steps_x: i32,
+    pub steps_y: i32,
+
+    /// Precise movement measured in `unit`s
+    pub x: f64,
+    pub y: f64,
+    pub unit: ScrollUnit,
+}
+
+
+
+impl std::fmt::Display for MouseButton {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Other(number) => write!(f, "button {number}"),
+            _ => write!(f, "{self:?}"),
+        }
+    }
+}
+
+
+impl std::fmt::Display for ScrollDelta {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let unit = match self.unit {
+            ScrollUnit::Lines => "lines",
+        };
+
+        write!(f, "({},{}) steps, ({:.2},{:.2}) {unit}", self.steps_x, self.steps_y, self.x, self.y)
+    }
+}
@@ -4,6 +4,19 @@
 use crate::types::*;
 
 
+/// Flags of WM_NORMAL_HINTS (ICCCM 4.1.2.3)
+const US_POSITION: u32 = 1 << 0;
+const P_MIN_SIZE: u32 = 1 << 4;
+const P_MAX_SIZE: u32 = 1 << 5;
+const P_RESIZE_INC: u32 = 1 << 6;
+const P_ASPECT: u32 = 1 << 7;
+const P_BASE_SIZE: u32 = 1 << 8;
+const P_WIN_GRAVITY: u32 = 1 << 9;
+
+/// The window manager places the content, not the frame, at the requested position
+const STATIC_GRAVITY: u32 = 10;
+
+
 
 impl ShellClient {
     pub fn new(info: &ShellClientInfo) -> Result<Self> {
@@ -22,10 +35,16 @@ impl ShellClient {
             fake_window_handle,
 
             text_input_subsystem: None,
+            xinput_subsystem: None,
+            sys_redraw_subsystem: None,
+            last_user_time: Cell::new(xcb::x::CURRENT_TIME),
+            redraw_windows: RefCell::new(Vec::new()),
+            xembed_clients: RefCell::new(HashMap::new()),
         };
 
         myself.init_global_subsystems(info.subsystems)?;
         myself.init_fake_window()?;
+        myself.init_keyboard()?;
 
         Ok(myself)
     }
@@ -46,12 +65,57 @@ impl ShellClient {
         
         X11WindowVisualInfo {
             visualid: screen.root_visual(),
-            colormap: screen.default_colormap(),
+            colormap: Some(screen.default_colormap()),
             color_depth: screen.root_depth(),
         }
     }
 
 
+    /// Returns a 32-bit TrueColor visual, whose unused bits hold alpha
+    pub(crate) fn get_transparent_visual_info(&self) -> Option<X11WindowVisualInfo> {
+        let depth = self.get_default_screen().allowed_depths()
+            .find(|depth| depth.depth() == 32)?;
+
+        let visual = depth.visuals().iter()
+            .find(|visual| visual.class() == xcb::x::VisualClass::TrueColor)?;
+
+        Some(X11WindowVisualInfo {
+            visualid: visual.visual_id(),
+            colormap: None,
+            color_depth: 32,
+        })
+    }
+
+
+    /// Returns `None` if the default screen has no such visual
+    pub(crate) fn get_visual_info(&self, visualid: xcb::x::Visualid) -> Option<X11WindowVisualInfo> {
+        let default_visual_info = self.get_default_visual_info();
+
+        if visualid == default_visual_info.visualid {
+            return Some(default_visual_info);
+        }
+
+        let depth = self.get_default_screen().allowed_depths()
+            .find(|depth| depth.visuals().iter().any(|visual| visual.visual_id() == visualid))?;
+
+        Some(X11WindowVisualInfo {
+            visualid,
+            colormap: None,
+            color_depth: depth.depth(),
+        })
+    }
+
+
+    pub(crate) fn get_window_visual_info(&self, info: &WindowInfo) -> Result<X11WindowVisualInfo> {
+        if info.is_transparent {
+            self.get_transparent_visual_info()
+                .ok_or(Error::PlatformApiFailed("cannot find visual with alpha channel"))
+        } else {
+            Ok(self.get_default_visual_info())
+        }
+    }
+
+
     pub(crate) fn get_default_wm_protocols(&self) -> Vec<xcb::x::Atom> {
         vec![
             self.atoms.WM_DELETE_WINDOW,
@@ -68,7 +132,15 @@ impl ShellClient {
 
 
     fn connect() -> Result<(xcb::Connection, i32)> {
-        xcb::Connection::connect_with_xlib_display()
+        let optional_extensions = [
+            xcb::Extension::Input,
+            xcb::Extension::Sync,
+            xcb::Extension::XFixes,
+            #[cfg(feature = "graphics_rawpix")]
+            xcb::Extension::Shm,
+        ];
+
+        xcb::Connection::connect_with_xlib_display_and_extensions(&[], &optional_extensions)
             .or_else(|_| Err(Error::PlatformApiFailed("cannot connect to Xorg")))
     }
 
@@ -115,6 +187,16 @@ impl ShellClient {
             self.text_input_subsystem = Some(X11GlobalTextInputSubsystem::new(&self)?);
         }
 
+        if subsystem_list.contains(&ShellSubsystem::MouseInput) {
+            // Core pointer events are used if XInput is not supported
+            self.xinput_subsystem = X11GlobalXInputSubsystem::new(self).ok();
+        }
+
+        if subsystem_list.contains(&ShellSubsystem::SysRedraw) {
+            // Redraws are never deferred if compositor changes cannot be tracked
+            self.sys_redraw_subsystem = X11GlobalSysRedrawSubsystem::new(self).ok();
+        }
+
         Ok(())
     }
 
@@ -142,16 +224,64 @@ impl ShellClient {
     }
 
 
-    fn create_window_handle(&self, info: &WindowInfo, visual_info: &X11WindowVisualInfo) -> Result<X11WindowHandle> {
+    fn create_colormap(&self, visual_info: &X11WindowVisualInfo) -> Result<xcb::x::Colormap> {
+        let colormap = self.connection.generate_id();
+
+        self.connection.send_and_check_request(&xcb::x::CreateColormap {
+            alloc: xcb::x::ColormapAlloc::None,
+            mid: colormap,
+            window: self.get_default_window(),
+            visual: visual_info.visualid,
+        })
+        .or_else(|_| Err(Error::PlatformApiFailed("cannot create colormap")))?;
+
+        Ok(colormap)
+    }
+
+
+    fn free_colormap(&self, colormap: xcb::x::Colormap) -> Result<()> {
+        self.connection.send_and_check_request(&xcb::x::FreeColormap {
+            cmap: colormap,
+        })
+        .or_else(|_| Err(Error::PlatformApiFailed("cannot free colormap")))
+    }
+
+
+    fn get_window_event_mask() -> xcb::x::EventMask {
+        xcb::x::EventMask::all().difference(
+            xcb::x::EventMask::SUBSTRUCTURE_NOTIFY
+            | xcb::x::EventMask::SUBSTRUCTURE_REDIRECT
+            // Redirected resizes are not performed by the server, window geometry is tracked with
+            // ConfigureNotify instead
+            | xcb::x::EventMask::RESIZE_REDIRECT
+            // Motion hints suppress all motion events but the first one
+            | xcb::x::EventMask::POINTER_MOTION_HINT
+        )
+    }
+
+
+    fn create_window_handle(&self, info: &WindowInfo, visual_info: &X11WindowVisualInfo, colormap: xcb::x::Colormap)
+        -> Result<X11WindowHandle>
+    {
+        use xcb::XidNew;
+
         let window_handle = self.connection.generate_id();
 
+        let position = info.position.unwrap_or(PixelPosition::new(0, 0));
+
+        let parent = match info.parent_id {
+            Some(parent_id) => unsafe { X11WindowHandle::new(parent_id as u32) },
+            None => self.get_default_window(),
+        };
+
         self.connection.send_and_check_request(&xcb::x::CreateWindow {
             wid: window_handle,
-            parent: self.get_default_window(),
+            parent,
             class: xcb::x::WindowClass::InputOutput,
             
-            x: 0, // Coordinates are often ignored
-            y: 0,
+            // Window managers ignore the coordinates unless WM_NORMAL_HINTS has USPosition
+            x: position.x as i16,
+            y: position.y as i16,
             width: info.size.width as u16,
             height: info.size.height as u16,
             border_width: 0,
@@ -160,11 +290,10 @@ impl ShellClient {
             visual: visual_info.visualid,
             value_list: &[
                 xcb::x::Cw::BackPixel(self.get_default_screen().black_pixel()),
-                xcb::x::Cw::EventMask(xcb::x::EventMask::all().difference(
-                    xcb::x::EventMask::SUBSTRUCTURE_NOTIFY
-                    | xcb::x::EventMask::SUBSTRUCTURE_REDIRECT
-                )),
-                xcb::x::Cw::Colormap(visual_info.colormap)
+                // The default border pixmap is inherited from the parent, which causes BadMatch if the depth differs
+                xcb::x::Cw::BorderPixel(0),
+                xcb::x::Cw::EventMask(Self::get_window_event_mask()),
+                xcb::x::Cw::Colormap(colormap)
             ]
         })
         .or_else(|_| Err(Error::PlatformApiFailed("cannot create window")))?;
@@ -209,9 +338,69 @@ impl ShellClient {
     }
 
 
-    fn get_window_initial_state(&self, window_handle: X11WindowHandle, info: &WindowInfo) -> X11WindowState {
-        X11WindowState {
-            size: info.size.clone()
+    /// `parent_position` is the position of the window relative to its parent
+    fn get_window_initial_state(
+        &self,
+        window_handle: X11WindowHandle,
+        size: PixelSize,
+        parent_position: PixelPosition,
+        is_child: bool,
+    ) -> Result<X11WindowState> {
+        let position = match is_child {
+            true => parent_position,
+            false => self.get_window_root_position(window_handle)?,
+        };
+
+        Ok(X11WindowState {
+            size,
+            position,
+            parent_position,
+            frame_extents: X11FrameExtents::default(),
+            pressed_keys: [false; 256],
+            damage: Vec::new(),
+        })
+    }
+
+
+    /// Returns the position of the window's top-left corner relative to the root window
+    pub(crate) fn get_window_root_position(&self, window_handle: X11WindowHandle) -> Result<PixelPosition> {
+        let cookie = self.connection.send_request(&xcb::x::TranslateCoordinates {
+            src_window: window_handle,
+            dst_window: self.get_default_window(),
+            src_x: 0,
+            src_y: 0,
+        });
+
+        let reply = self.connection.wait_for_reply(cookie)
+            .or_else(|_| Err(Error::PlatformApiFailed("cannot get window position")))?;
+
+        Ok(PixelPosition::new(reply.dst_x() as i32, reply.dst_y() as i32))
+    }
+
+
+    /// Returns zero extents if the window manager has not set them
+    pub(crate) fn get_window_frame_extents(&self, window_handle: X11WindowHandle) -> Result<X11FrameExtents> {
+        let cookie = self.connection.send_request(&xcb::x::GetProperty {
+            delete: false,
+            window: window_handle,
+            property: self.atoms._NET_FRAME_EXTENTS,
+            r#type: xcb::x::ATOM_CARDINAL,
+            long_offset: 0,
+            long_length: 4,
+        });
+
+        let reply = self.connection.wait_for_reply(cookie)
+            .or_else(|_| Err(Error::PlatformApiFailed("cannot get frame extents")))?;
+
+        match reply.value::<u32>() {
+            [left, right, top, bottom] => Ok(X11FrameExtents {
+                left: *left,
+                right: *right,
+                top: *top,
+                bottom: *bottom,
+            }),
+
+            _ => Ok(X11FrameExtents::default()),
         }
     }
 
@@ -232,24 +421,204 @@ impl ShellClient {
     }
 
 
+    /// Writes WM_NORMAL_HINTS. Non-resizable windows have the minimum and maximum sizes set to the last requested
+    /// size, or to the current size if no request is pending.
+    pub(crate) fn update_window_size_hints(&self, window: &Window) -> Result<()> {
+        let constraints = &window.size_constraints;
+        let fixed_size = window.pending_size.as_ref().unwrap_or(&window.state.size);
+
+        let (min_size, max_size) = match window.is_resizable {
+            true => (constraints.min_size.clone(), constraints.max_size.clone()),
+            false => (Some(fixed_size.clone()), Some(fixed_size.clone())),
+        };
+
+        // flags, x, y, width, height, min size, max size, increment, min aspect, max aspect, base size, gravity
+        let mut hints = [0u32; 18];
+
+        // The coordinates are obsolete, but some window managers still read them
+        if let Some(position) = window.requested_position {
+            hints[0] |= US_POSITION | P_WIN_GRAVITY;
+            hints[1..3].copy_from_slice(&[position.x as u32, position.y as u32]);
+            hints[17] = STATIC_GRAVITY;
+        }
+
+        if let Some(size) = min_size {
+            hints[0] |= P_MIN_SIZE;
+            hints[5..7].copy_from_slice(&[size.width, size.height]);
+        }
+
+        if let Some(size) = max_size {
+            hints[0] |= P_MAX_SIZE;
+            hints[7..9].copy_from_slice(&[size.width, size.height]);
+        }
+
+        if let Some(size) = &constraints.size_increment {
+            hints[0] |= P_RESIZE_INC;
+            hints[9..11].copy_from_slice(&[size.width, size.height]);
+        }
+
+        if let Some((min, max)) = &constraints.aspect_ratio {
+            hints[0] |= P_ASPECT;
+            hints[11..15].copy_from_slice(&[min.width, min.height, max.width, max.height]);
+        }
+
+        if let Some(size) = &constraints.base_size {
+            hints[0] |= P_BASE_SIZE;
+            hints[15..17].copy_from_slice(&[size.width, size.height]);
+        }
+
+        self.connection.send_and_check_request(&xcb::x::ChangeProperty {
+            mode: xcb::x::PropMode::Replace,
+            window: window.handle,
+            property: xcb::x::ATOM_WM_NORMAL_HINTS,
+            r#type: xcb::x::ATOM_WM_SIZE_HINTS,
+            data: &hints
+        })
+        .or_else(|_| Err(Error::PlatformApiFailed("cannot set size hints")))
+    }
+
+
     pub(crate) fn new_window(&self, info: &WindowInfo, visual_info: &X11WindowVisualInfo) -> Result<Window> {
-        let window_handle = self.create_window_handle(info, visual_info)?;
+        let (colormap, owned_colormap) = match visual_info.colormap {
+            Some(colormap) => (colormap, None),
+            None => {
+                let colormap = self.create_colormap(visual_info)?;
+                (colormap, Some(colormap))
+            }
+        };
+
+        let window_handle = match self.create_window_handle(info, visual_info, colormap) {
+            Ok(window_handle) => window_handle,
+            Err(error) => {
+                if let Some(colormap) = owned_colormap {
+                    self.free_colormap(colormap)?;
+                }
+                return Err(error);
+            }
+        };
+
         self.set_window_class(window_handle)?;
         self.set_window_protocols(window_handle, &self.get_default_wm_protocols())?;
-        let state = self.get_window_initial_state(window_handle, info);
 
-        Ok(Window {
+        if let Some(xinput) = &self.xinput_subsystem {
+            xinput.init_for_window(self, window_handle)?;
+        }
+
+        let parent_position = info.position.unwrap_or(PixelPosition::new(0, 0));
+        let is_child = info.parent_id.is_some();
+        let state = self.get_window_initial_state(window_handle, info.size.clone(), parent_position, is_child)?;
+
+        let window = Window {
             handle: window_handle,
             state,
             text_input: None,
             sys_redraw: None,
+            visualid: visual_info.visualid,
+            is_foreign: false,
+            is_child,
+            xembed_embedder: None,
+            xembed_client: None,
+            size_constraints: info.size_constraints.clone(),
+            is_resizable: info.is_resizable,
+            pending_size: None,
+            requested_position: info.position,
+            colormap: owned_colormap,
+        };
+
+        self.update_window_size_hints(&window)?;
+
+        Ok(window)
+    }
+
+
+    /// Wraps a window created by another party. Its events are delivered to this client until the window is dropped.
+    pub(crate) fn adopt_window(&self, window_handle: X11WindowHandle) -> Result<Window> {
+        let geometry_cookie = self.connection.send_request(&xcb::x::GetGeometry {
+            drawable: xcb::x::Drawable::Window(window_handle),
+        });
+
+        let attributes_cookie = self.connection.send_request(&xcb::x::GetWindowAttributes {
+            window: window_handle,
+        });
+
+        let geometry = self.connection.wait_for_reply(geometry_cookie)
+            .or_else(|_| Err(Error::PlatformApiFailed("cannot get window geometry")))?;
+
+        let attributes = self.connection.wait_for_reply(attributes_cookie)
+            .or_else(|_| Err(Error::PlatformApiFailed("cannot get window attributes")))?;
+
+        self.select_foreign_window_events(window_handle)?;
+
+        // The owner of the window may have selected XInput button events already,
+        // core pointer events are received then
+        if let Some(xinput) = &self.xinput_subsystem {
+            xinput.init_for_window(self, window_handle).ok();
+        }
+
+        let size = PixelSize::new(geometry.width() as u32, geometry.height() as u32);
+        let parent_position = PixelPosition::new(geometry.x() as i32, geometry.y() as i32);
+
+        Ok(Window {
+            handle: window_handle,
+            state: self.get_window_initial_state(window_handle, size, parent_position, false)?,
+            text_input: None,
+            sys_redraw: None,
+            visualid: attributes.visual(),
+            is_foreign: true,
+            is_child: false,
+            xembed_embedder: None,
+            xembed_client: None,
+            size_constraints: SizeConstraints::default(),
+            is_resizable: true,
+            pending_size: None,
+            requested_position: None,
+            colormap: None,
         })
     }
 
 
+    /// Only one client can select button presses on a window, so they are not received if the owner of the window
+    /// has already selected them
+    fn select_foreign_window_events(&self, window_handle: X11WindowHandle) -> Result<()> {
+        let select_events = |event_mask| {
+            self.connection.send_and_check_request(&xcb::x::ChangeWindowAttributes {
+                window: window_handle,
+                value_list: &[xcb::x::Cw::EventMask(event_mask)],
+            })
+        };
+
+        select_events(Self::get_window_event_mask())
+            .or_else(|_| select_events(Self::get_window_event_mask().difference(xcb::x::EventMask::BUTTON_PRESS)))
+            .or_else(|_| Err(Error::PlatformApiFailed("cannot select window events")))
+    }
+
+
     pub(crate) fn drop_window(&self, mut window: Window) -> Result<()> {
         self.destroy_window_subsystems(&mut window)?;
-        self.destroy_window_handle(window.handle)?;
+
+        // The embedded window belongs to another application and must survive the embedder
+        if window.xembed_embedder.as_ref().is_some_and(|embedder| embedder.client.is_some()) {
+            self.release_xembed_client(&mut window)?;
+        }
+
+        if window.is_foreign {
+            if let Some(xinput) = &self.xinput_subsystem {
+                xinput.deinit_for_window(self, window.handle)?;
+            }
+
+            self.connection.send_and_check_request(&xcb::x::ChangeWindowAttributes {
+                window: window.handle,
+                value_list: &[xcb::x::Cw::EventMask(xcb::x::EventMask::NO_EVENT)],
+            })
+            .or_else(|_| Err(Error::PlatformApiFailed("cannot deselect window events")))?;
+        } else {
+            self.destroy_window_handle(window.handle)?;
+        }
+
+        if let Some(colormap) = window.colormap {
+            self.free_colormap(colormap)?;
+        }
+
         Ok(())
     }
 
@@ -265,6 +634,16 @@ impl ShellClient {
     }
 
 
+    /// Must be called by canvases after a frame is presented.
+    /// Lets the shell know that the window content matches the last requested size.
+    pub(crate) fn finish_frame(&self, window: &mut Window) -> Result<()> {
+        match &mut window.sys_redraw {
+            Some(sys_redraw) => sys_redraw.unlock_surface(self),
+            None => Ok(()),
+        }
+    }
+
+
     fn poll_for_event(&self) -> Result<Option<xcb::Event>> {
         self.connection.poll_for_event()
             .or_else(|_| Err(Error::PlatformApiFailed("cannot poll for event")))
@@ -278,7 +657,8 @@ impl ShellClient {
 
 
     fn make_shell_event(&self, event: xcb::Event) -> ShellMessage {
-        let window_handle = self.get_event_window_handle(&event);
+        let window_handle = self.get_event_window_handle(&event)
+            .map(|handle| self.xembed_clients.borrow().get(&handle).copied().unwrap_or(handle));
 
         let is_global = if let Some(handle) = window_handle {
             handle == self.fake_window_handle
@@ -290,6 +670,102 @@ impl ShellClient {
     }
 
 
+    /// Input method callbacks are not bound to the messages that trigger them, so the collected pre-edit events are
+    /// delivered with artificial messages
+    fn make_text_input_messages(&self) -> Vec<ShellMessage> {
+        let preedit_windows = match &self.text_input_subsystem {
+            Some(subsystem) => subsystem.preedit_windows.take(),
+            None => return Vec::new(),
+        };
+
+        preedit_windows.into_iter()
+            .map(|window_handle| self.make_internal_message(window_handle, self.atoms.FEJIXWM_TEXT_INPUT_EVENT))
+            .collect()
+    }
+
+
+    /// Redraw requests are delivered with artificial messages at the end of the loop iteration
+    fn make_redraw_messages(&self) -> Vec<ShellMessage> {
+        self.redraw_windows.take().into_iter()
+            .map(|window_handle| self.make_internal_message(window_handle, self.atoms.FEJIXWM_REDRAW_EVENT))
+            .collect()
+    }
+
+
+    /// Returns true if internal messages wait to be delivered, so the loop must not block waiting for events.
+    ///
+    /// The client cannot be shared between threads, so requests made outside the callback (e.g. before the loop
+    /// starts) are found here as well and no wake-up event is needed.
+    fn has_internal_messages(&self) -> bool {
+        let has_preedit_windows = self.text_input_subsystem.as_ref()
+            .is_some_and(|subsystem| !subsystem.preedit_windows.borrow().is_empty());
+
+        has_preedit_windows || !self.redraw_windows.borrow().is_empty()
+    }
+
+
+    /// Makes a message that is never sent to the server
+    fn make_internal_message(&self, window_handle: X11WindowHandle, message_type: xcb::x::Atom) -> ShellMessage {
+        let event = xcb::x::ClientMessageEvent::new(
+            window_handle,
+            message_type,
+            xcb::x::ClientMessageData::Data32([0; 5])
+        );
+
+        self.make_shell_event(xcb::Event::X(xcb::x::Event::ClientMessage(event)))
+    }
+
+
+    /// Returns true if the event is consumed by the input method and must not be processed
+    fn filter_event(&self, event: &xcb::Event) -> bool {
+        if self.text_input_subsystem.is_none() {
+            return false;
+        }
+
+        match self.make_xlib_event(event) {
+            Some(mut xevent) => unsafe { xlib::XFilterEvent(&mut xevent, 0) != 0 },
+            None => false,
+        }
+    }
+
+
+    /// Converts a core X event to its Xlib representation using the converter that Xlib has registered for
+    /// the event type
+    fn make_xlib_event(&self, event: &xcb::Event) -> Option<xlib::XEvent> {
+        let raw_event = match event {
+            xcb::Event::X(event) => event.as_raw(),
+            _ => return None,
+        };
+
+        // The wire representation of a core event is 32 bytes
+        let mut wire_event = [0u8; 32];
+
+        unsafe {
+            std::ptr::copy_nonoverlapping(raw_event as *const u8, wire_event.as_mut_ptr(), wire_event.len());
+        }
+
+        let event_type = (wire_event[0] & 0x7F) as i32;
+
+        // Xlib expands sequence numbers relatively to the last processed request
+        let sequence = unsafe { xlib::XLastKnownRequestProcessed(self.xdisplay) } as u16;
+        wire_event[2..4].copy_from_slice(&sequence.to_ne_bytes());
+
+        unsafe {
+            let converter = xlib::XESetWireToEvent(self.xdisplay, event_type, None)?;
+            xlib::XESetWireToEvent(self.xdisplay, event_type, Some(converter));
+
+            let mut xevent: xlib::XEvent = std::mem::zeroed();
+            let is_converted = converter(self.xdisplay, &mut xevent, wire_event.as_mut_ptr() as *mut xlib::xEvent);
+
+            if is_converted != 0 {
+                Some(xevent)
+            } else {
+                None
+            }
+        }
+    }
+
+
     fn user_data_to_event_payload(data: Option<Box<dyn Any>>) -> [u8; 20] {
         let data_address: usize = if let Some(data) = data {
             // Wrap data in a box again so that the wide pointer is not accidentally converted to a thin pointer
@@ -342,6 +818,19 @@ impl WindowTrait for Window {
         self.state.size.clone()
     }
 
+    fn get_position(&self) -> PixelPosition {
+        self.state.position
+    }
+
+    fn get_outer_size(&self) -> PixelSize {
+        let extents = &self.state.frame_extents;
+
+        PixelSize::new(
+            self.state.size.width + extents.left + extents.right,
+            self.state.size.height + extents.top + extents.bottom,
+        )
+    }
+
 }
 
 
@@ -376,7 +865,23 @@ impl ShellClientTrait for ShellClient {
         let reply = self.connection.wait_for_reply(cookie)
             .or_else(|_| Err(Error::PlatformApiFailed("cannot get window size")))?;
 
-        Ok(PixelSize::new(reply.x() as u32, reply.y() as u32))
+        Ok(PixelSize::new(reply.width() as u32, reply.height() as u32))
+    }
+
+
+    fn request_redraw(&self, window: &Self::Window) -> Result<()> {
+        let mut redraw_windows = self.redraw_windows.borrow_mut();
+
+        if !redraw_windows.contains(&window.handle) {
+            redraw_windows.push(window.handle);
+        }
+
+        Ok(())
+    }
+
+
+    fn notify_frame_presented(&self, window: &mut Self::Window) -> Result<()> {
+        self.finish_frame(window)
     }
 
 
@@ -411,19 +916,40 @@ impl ShellClientTrait for ShellClient {
                 break;
             }
 
+            // The callback is called without a message only if it peeks
+            let mut has_internal_messages = false;
+
             match settings.behavior {
                 ListeningBehavior::Peek => {
                     event = self.poll_for_event()?;
                 }
 
+                ListeningBehavior::Await if self.has_internal_messages() => {
+                    event = self.poll_for_event()?;
+                    has_internal_messages = true;
+                }
+
                 ListeningBehavior::Await => {
                     event = Some(self.wait_for_event()?);
                 }
             }
 
-            let shell_event = event.and_then(|event| Some(self.make_shell_event(event)));
+            let is_filtered = event.as_ref().is_some_and(|event| self.filter_event(event));
+
+            let is_empty_wakeup = has_internal_messages && event.is_none();
+
+            if !(is_filtered || is_empty_wakeup) {
+                let shell_event = event.and_then(|event| Some(self.make_shell_event(event)));
+                callback(shell_event.as_ref(), &mut settings);
+            }
+
+            for message in self.make_text_input_messages() {
+                callback(Some(&message), &mut settings);
+            }
 
-            callback(shell_event.as_ref(), &mut settings);
+            for message in self.make_redraw_messages() {
+                callback(Some(&message), &mut settings);
+            }
         }
 
         Ok(())
@@ -442,6 +968,9 @@ impl ShellClientTrait for ShellClient {
     fn is_subsystem_available(&self, subsystem: ShellSubsystem) -> bool {
         match subsystem {
             ShellSubsystem::TextInput => { self.text_input_subsystem.is_some() },
+            ShellSubsystem::SysRedraw => {
+                self.connection.active_extensions().any(|extension| extension == xcb::Extension::Sync)
+            },
             _ => true
         }
     }
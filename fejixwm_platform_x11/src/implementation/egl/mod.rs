@@ -0,0 +1,398 @@
+pub use crate::core::interface::opengl::*;
+
+use crate::{
+    *,
+    errors::*,
+};
+
+use super::gl_extensions::*;
+
+use khronos_egl as egl;
+
+use std::{
+    ffi::c_void,
+    os::raw::c_ulong,
+};
+
+
+/// OpenGL and OpenGL ES canvas based on EGL. Works with Mesa software renderers.
+pub struct EglCanvas {
+    display: egl::Display,
+    config: egl::Config,
+    context: egl::Context,
+    surface: egl::Surface,
+
+    /// The current API is a thread-local state of EGL, so it is bound again before the context is used
+    api: egl::Enum,
+}
+
+
+/// Defined by EGL_KHR_platform_x11, the native display is an Xlib `Display*`
+const EGL_PLATFORM_X11_KHR: egl::Enum = 0x31D5;
+const EGL_PLATFORM_X11_SCREEN_KHR: egl::Attrib = 0x31D6;
+
+const EGL_OPENGL_ES_BIT: egl::Int = 0x0001;
+
+
+
+impl OpenglCanvasTrait for EglCanvas {
+
+    fn new_shared(
+        client: &Self::ShellClient,
+        window_info: &WindowInfo,
+        canvas_info: &Self::CanvasInfo,
+        shared_canvas: &Self
+    )
+        -> Result<(Self::Window, Self)>
+    {
+        Self::new_with_shared_context(client, window_info, canvas_info, Some(shared_canvas.context))
+    }
+
+
+    fn make_current(&self, client: &Self::ShellClient, window: &mut Self::Window) -> Result<()> {
+        self.bind_api()?;
+
+        egl::API.make_current(self.display, Some(self.surface), Some(self.surface), Some(self.context))
+            .or_else(|_| Err(Error::GraphicsApiFailed("cannot make EGL context current")))
+    }
+
+
+    fn swap_buffers(&self, client: &Self::ShellClient, window: &mut Self::Window) -> Result<()> {
+        self.assert_current()?;
+
+        egl::API.swap_buffers(self.display, self.surface)
+            .or_else(|_| Err(Error::GraphicsApiFailed("cannot swap EGL buffers")))?;
+
+        client.finish_frame(window)
+    }
+
+
+    /// Adaptive vsync is not supported by EGL
+    fn set_swap_interval(
+        &self,
+        client: &Self::ShellClient,
+        window: &mut Self::Window,
+        swap_interval: OpenglSwapInterval
+    )
+        -> Result<()>
+    {
+        self.assert_current()?;
+
+        let interval = match swap_interval {
+            OpenglSwapInterval::Immediate => 0,
+            OpenglSwapInterval::Vsync => 1,
+            OpenglSwapInterval::AdaptiveVsync => {
+                return Err(Error::GraphicsApiFailed("adaptive vsync is not supported by EGL"));
+            }
+        };
+
+        egl::API.swap_interval(self.display, interval)
+            .or_else(|_| Err(Error::GraphicsApiFailed("cannot set swap interval")))
+    }
+
+
+    fn load_function(&self, client: &Self::ShellClient, window: &Self::Window, function_name: &str)
+        -> Result<*const c_void>
+    {
+        self.assert_current()?;
+
+        match egl::API.get_proc_address(function_name) {
+            Some(function) => Ok(function as *const c_void),
+            None => Err(Error::InvalidArgument),
+        }
+    }
+
+
+    /// Names starting with `EGL_` are looked up among the EGL extensions of the display
+    fn is_extension_supported(&self, client: &Self::ShellClient, window: &Self::Window, extension_name: &str)
+        -> Result<bool>
+    {
+        self.assert_current()?;
+
+        if extension_name.starts_with("EGL_") {
+            return Ok(Self::is_egl_extension_supported(Some(self.display), extension_name));
+        }
+
+        unsafe {
+            is_gl_extension_supported(
+                |function_name| {
+                    let function = egl::API.get_proc_address(function_name.to_str().ok()?)?;
+                    Some(function as *const c_void)
+                },
+                extension_name
+            )
+        }
+    }
+
+}
+
+
+impl CanvasTrait for EglCanvas {
+
+    type ShellClient = ShellClient;
+    type Window = Window;
+
+    type CanvasInfo = OpenglInfo;
+
+    fn new(client: &Self::ShellClient, window_info: &WindowInfo, canvas_info: &Self::CanvasInfo)
+        -> Result<(Self::Window, Self)>
+    {
+        Self::new_with_shared_context(client, window_info, canvas_info, None)
+    }
+
+
+    fn drop(self, client: &Self::ShellClient, window: Self::Window) -> Result<()> {
+        self.bind_api()?;
+
+        if egl::API.get_current_context() == Some(self.context) {
+            egl::API.make_current(self.display, None, None, None)
+                .or_else(|_| Err(Error::GraphicsApiFailed("cannot release EGL context")))?;
+        }
+
+        egl::API.destroy_surface(self.display, self.surface)
+            .or_else(|_| Err(Error::GraphicsApiFailed("cannot destroy EGL surface")))?;
+
+        egl::API.destroy_context(self.display, self.context)
+            .or_else(|_| Err(Error::GraphicsApiFailed("cannot destroy EGL context")))?;
+
+        // The display is not terminated because it is shared by all canvases of the client
+        client.drop_window(window)
+    }
+
+}
+
+
+impl EglCanvas {
+
+    fn new_with_shared_context(
+        client: &ShellClient,
+        window_info: &WindowInfo,
+        canvas_info: &OpenglInfo,
+        shared_context: Option<egl::Context>
+    )
+        -> Result<(Window, Self)>
+    {
+        let display = Self::get_display(client)?;
+
+        let api = match canvas_info.api {
+            OpenglApi::Opengl => egl::OPENGL_API,
+            OpenglApi::OpenglEs => egl::OPENGL_ES_API,
+        };
+
+        egl::API.bind_api(api)
+            .or_else(|_| Err(Error::GraphicsApiFailed("requested API is not supported by EGL")))?;
+
+        let (config, visual_info) = Self::choose_config(client, display, window_info, canvas_info)?;
+        let context = Self::create_context(display, config, canvas_info, shared_context)?;
+
+        let window = match client.new_window(window_info, &visual_info) {
+            Ok(window) => window,
+            Err(error) => {
+                egl::API.destroy_context(display, context).ok();
+                return Err(error);
+            }
+        };
+
+        match Self::create_surface(display, config, &window, canvas_info) {
+            Ok(surface) => Ok((window, Self { display, config, context, surface, api })),
+            Err(error) => {
+                egl::API.destroy_context(display, context).ok();
+                client.drop_window(window)?;
+                Err(error)
+            }
+        }
+    }
+
+
+    /// Returns the same display for all canvases of a client. Initializing it again has no effect.
+    fn get_display(client: &ShellClient) -> Result<egl::Display> {
+        let is_platform_supported = Self::is_egl_extension_supported(None, "EGL_KHR_platform_x11")
+            || Self::is_egl_extension_supported(None, "EGL_EXT_platform_x11");
+
+        if !is_platform_supported {
+            return Err(Error::GraphicsApiFailed("EGL does not support X11"));
+        }
+
+        let attributes = [
+            EGL_PLATFORM_X11_SCREEN_KHR, client.default_screen_number as egl::Attrib,
+            egl::ATTRIB_NONE
+        ];
+
+        let display = unsafe {
+            egl::API.get_platform_display(EGL_PLATFORM_X11_KHR, client.xdisplay as *mut c_void, &attributes)
+        }
+        .or_else(|_| Err(Error::GraphicsApiFailed("cannot get EGL display")))?;
+
+        egl::API.initialize(display)
+            .or_else(|_| Err(Error::GraphicsApiFailed("cannot initialize EGL display")))?;
+
+        Ok(display)
+    }
+
+
+    fn get_config_attributes(canvas_info: &OpenglInfo) -> Vec<egl::Int> {
+        let renderable_type = match canvas_info.api {
+            OpenglApi::Opengl => egl::OPENGL_BIT,
+            OpenglApi::OpenglEs if canvas_info.major_version >= 3 => egl::OPENGL_ES3_BIT,
+            OpenglApi::OpenglEs if canvas_info.major_version == 2 => egl::OPENGL_ES2_BIT,
+            OpenglApi::OpenglEs => EGL_OPENGL_ES_BIT,
+        };
+
+        let mut attributes = vec![
+            egl::SURFACE_TYPE, egl::WINDOW_BIT,
+            egl::RENDERABLE_TYPE, renderable_type,
+            egl::CONFORMANT, renderable_type,
+            egl::RED_SIZE, canvas_info.red_bits as egl::Int,
+            egl::GREEN_SIZE, canvas_info.green_bits as egl::Int,
+            egl::BLUE_SIZE, canvas_info.blue_bits as egl::Int,
+            egl::ALPHA_SIZE, canvas_info.alpha_bits as egl::Int,
+            egl::DEPTH_SIZE, canvas_info.depth_bits as egl::Int,
+            egl::STENCIL_SIZE, canvas_info.stencil_bits as egl::Int,
+        ];
+
+        if canvas_info.multisamples > 0 {
+            attributes.extend_from_slice(&[
+                egl::SAMPLE_BUFFERS, 1,
+                egl::SAMPLES, canvas_info.multisamples as egl::Int,
+            ]);
+        }
+
+        attributes.push(egl::NONE);
+        attributes
+    }
+
+
+    /// Transparent windows require a 32-bit visual, opaque windows prefer the visuals of the root depth so that the
+    /// compositor does not blend them
+    fn choose_config(client: &ShellClient, display: egl::Display, window_info: &WindowInfo, canvas_info: &OpenglInfo)
+        -> Result<(egl::Config, X11WindowVisualInfo)>
+    {
+        let attributes = Self::get_config_attributes(canvas_info);
+
+        let config_count = egl::API.matching_config_count(display, &attributes)
+            .or_else(|_| Err(Error::GraphicsApiFailed("cannot find suitable EGL configuration")))?;
+
+        let mut configs = Vec::with_capacity(config_count);
+
+        egl::API.choose_config(display, &attributes, &mut configs)
+            .or_else(|_| Err(Error::GraphicsApiFailed("cannot find suitable EGL configuration")))?;
+
+        let preferred_depth = if window_info.is_transparent {
+            32
+        } else {
+            client.get_default_screen().root_depth()
+        };
+
+        // The list is sorted from the best to the worst configuration
+        configs.into_iter()
+            .filter_map(|config| {
+                let visualid = egl::API.get_config_attrib(display, config, egl::NATIVE_VISUAL_ID).ok()?;
+                Some((config, client.get_visual_info(visualid as xcb::x::Visualid)?))
+            })
+            .find(|(_, visual_info)| visual_info.color_depth == preferred_depth)
+            .ok_or(Error::GraphicsApiFailed("cannot find EGL configuration for the window"))
+    }
+
+
+    fn create_context(
+        display: egl::Display,
+        config: egl::Config,
+        canvas_info: &OpenglInfo,
+        shared_context: Option<egl::Context>
+    )
+        -> Result<egl::Context>
+    {
+        let mut attributes = vec![
+            egl::CONTEXT_MAJOR_VERSION, canvas_info.major_version as egl::Int,
+            egl::CONTEXT_MINOR_VERSION, canvas_info.minor_version as egl::Int,
+        ];
+
+        if canvas_info.api == OpenglApi::Opengl {
+            let profile = if canvas_info.compatibility_flag {
+                egl::CONTEXT_OPENGL_COMPATIBILITY_PROFILE_BIT
+            } else {
+                egl::CONTEXT_OPENGL_CORE_PROFILE_BIT
+            };
+
+            attributes.extend_from_slice(&[egl::CONTEXT_OPENGL_PROFILE_MASK, profile]);
+        }
+
+        if canvas_info.debug_flag {
+            attributes.extend_from_slice(&[egl::CONTEXT_OPENGL_DEBUG, egl::TRUE as egl::Int]);
+        }
+
+        if canvas_info.robustness_flag {
+            attributes.extend_from_slice(&[
+                egl::CONTEXT_OPENGL_ROBUST_ACCESS, egl::TRUE as egl::Int,
+                egl::CONTEXT_OPENGL_RESET_NOTIFICATION_STRATEGY, egl::LOSE_CONTEXT_ON_RESET,
+            ]);
+        }
+
+        attributes.push(egl::NONE);
+
+        egl::API.create_context(display, config, shared_context, &attributes)
+            .or_else(|_| Err(Error::GraphicsApiFailed("cannot create EGL context")))
+    }
+
+
+    fn create_surface(display: egl::Display, config: egl::Config, window: &Window, canvas_info: &OpenglInfo)
+        -> Result<egl::Surface>
+    {
+        use xcb::Xid;
+
+        let render_buffer = if canvas_info.framebuffers >= 2 {
+            egl::BACK_BUFFER
+        } else {
+            egl::SINGLE_BUFFER
+        };
+
+        let color_space = if canvas_info.srgb_flag {
+            egl::GL_COLORSPACE_SRGB
+        } else {
+            egl::GL_COLORSPACE_LINEAR
+        };
+
+        let attributes = [
+            egl::RENDER_BUFFER as egl::Attrib, render_buffer as egl::Attrib,
+            egl::GL_COLORSPACE as egl::Attrib, color_space as egl::Attrib,
+            egl::ATTRIB_NONE
+        ];
+
+        // EGL_KHR_platform_x11 takes a pointer to the Xlib window
+        let mut xwindow = window.handle.resource_id() as c_ulong;
+
+        unsafe {
+            egl::API.create_platform_window_surface(
+                display, config, &mut xwindow as *mut c_ulong as *mut c_void, &attributes
+            )
+        }
+        .or_else(|_| Err(Error::GraphicsApiFailed("cannot create EGL window surface")))
+    }
+
+
+    fn bind_api(&self) -> Result<()> {
+        egl::API.bind_api(self.api)
+            .or_else(|_| Err(Error::GraphicsApiFailed("cannot bind EGL API")))
+    }
+
+
+    /// The current context is queried for the API of this canvas
+    fn assert_current(&self) -> Result<()> {
+        self.bind_api()?;
+
+        if egl::API.get_current_context() == Some(self.context) {
+            Ok(())
+        } else {
+            Err(Error::GraphicsApiFailed("EGL context is not current"))
+        }
+    }
+
+
+    /// `None` checks the client extensions
+    fn is_egl_extension_supported(display: Option<egl::Display>, extension_name: &str) -> bool {
+        egl::API.query_string(display, egl::EXTENSIONS)
+            .map(|extensions| is_name_in_list(extensions, extension_name))
+            .unwrap_or(false)
+    }
+
+}
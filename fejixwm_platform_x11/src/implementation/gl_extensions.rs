@@ -0,0 +1,68 @@
+//! Extension queries shared by the GLX and EGL canvases
+
+use crate::errors::*;
+
+use std::{
+    ffi::{c_void, CStr},
+    os::raw::{c_char, c_int, c_uint},
+};
+
+
+type GlGetString = unsafe extern "C" fn(c_uint) -> *const u8;
+type GlGetStringi = unsafe extern "C" fn(c_uint, c_uint) -> *const u8;
+type GlGetIntegerv = unsafe extern "C" fn(c_uint, *mut c_int);
+
+const GL_EXTENSIONS: c_uint = 0x1F03;
+const GL_NUM_EXTENSIONS: c_uint = 0x821D;
+
+
+/// Checks the extensions of the current context. `load_function` returns the address of a GL function.
+///
+/// # Safety
+///
+/// A context must be current and `load_function` must return functions of that context.
+pub(crate) unsafe fn is_gl_extension_supported(
+    load_function: impl Fn(&CStr) -> Option<*const c_void>,
+    extension_name: &str
+)
+    -> Result<bool>
+{
+    // Core profiles do not support querying all extensions as a single string
+    if let Some(get_string_i) = load_function(c"glGetStringi") {
+        let get_string_i: GlGetStringi = std::mem::transmute(get_string_i);
+
+        let get_integer_v: GlGetIntegerv = std::mem::transmute(
+            load_function(c"glGetIntegerv").ok_or(Error::GraphicsApiFailed("cannot load glGetIntegerv"))?
+        );
+
+        let mut extension_count: c_int = 0;
+        get_integer_v(GL_NUM_EXTENSIONS, &mut extension_count);
+
+        let is_supported = (0..extension_count.max(0) as c_uint)
+            .map(|index| get_string_i(GL_EXTENSIONS, index))
+            .filter(|name| !name.is_null())
+            .any(|name| CStr::from_ptr(name as *const c_char).to_bytes() == extension_name.as_bytes());
+
+        return Ok(is_supported);
+    }
+
+    let get_string: GlGetString = std::mem::transmute(
+        load_function(c"glGetString").ok_or(Error::GraphicsApiFailed("cannot load glGetString"))?
+    );
+
+    let extensions = get_string(GL_EXTENSIONS);
+
+    if extensions.is_null() {
+        return Err(Error::GraphicsApiFailed("cannot get OpenGL extensions"));
+    }
+
+    Ok(is_name_in_list(CStr::from_ptr(extensions as *const c_char), extension_name))
+}
+
+
+/// Extension lists are separated by spaces
+pub(crate) fn is_name_in_list(list: &CStr, name: &str) -> bool {
+    list.to_bytes()
+        .split(|character| *character == b' ')
+        .any(|list_name| list_name == name.as_bytes())
+}
@@ -1,3 +1,19 @@
+#[cfg(feature = "graphics_rawpix")]
 pub mod rawpix;
+
+#[cfg(feature = "graphics_opengl")]
+pub mod opengl;
+
+#[cfg(feature = "graphics_egl")]
+pub mod egl;
+
+#[cfg(feature = "graphics_vulkan")]
+pub mod vulkan;
+
+#[cfg(any(feature = "graphics_opengl", feature = "graphics_egl"))]
+mod gl_extensions;
+
 pub mod null_canvas;
-mod window_manip;
\ No newline at end of file
+pub mod xembed;
+mod window_manip;
+mod text_input;
\ No newline at end of file
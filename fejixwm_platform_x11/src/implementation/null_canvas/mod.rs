@@ -20,7 +20,7 @@ impl CanvasTrait for NullCanvas {
     fn new(client: &Self::ShellClient, window_info: &WindowInfo, canvas_info: &Self::CanvasInfo)
         -> Result<(Self::Window, Self)>
     {
-        let window = client.new_window(window_info, &client.get_default_visual_info())?;
+        let window = client.new_window(window_info, &client.get_window_visual_info(window_info)?)?;
         Ok((window, Self))
     }
 
@@ -0,0 +1,474 @@
+pub use crate::core::interface::opengl::*;
+
+use crate::{
+    *,
+    errors::*,
+};
+
+use super::gl_extensions::*;
+
+use x11::glx;
+
+use std::{
+    ffi::{c_void, CStr, CString},
+    os::raw::{c_int, c_uint},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+
+/// OpenGL canvas based on GLX. Does not require hardware acceleration, so it works on Mesa software renderers.
+pub struct OpenglCanvas {
+    context: glx::GLXContext,
+    fbconfig: glx::GLXFBConfig,
+}
+
+
+type GlxCreateContextAttribsArb = unsafe extern "C" fn(
+    *mut xlib::Display, glx::GLXFBConfig, glx::GLXContext, c_int, *const c_int
+) -> glx::GLXContext;
+
+type GlxSwapIntervalExt = unsafe extern "C" fn(*mut xlib::Display, glx::GLXDrawable, c_int);
+type GlxSwapIntervalMesa = unsafe extern "C" fn(c_uint) -> c_int;
+
+/// Selects OpenGL ES 2.0 and newer, defined by GLX_EXT_create_context_es2_profile
+const GLX_CONTEXT_ES2_PROFILE_BIT_EXT: c_int = 0x0004;
+
+/// Defined by GLX_ARB_create_context_robustness
+const GLX_CONTEXT_ROBUST_ACCESS_BIT_ARB: c_int = 0x0004;
+const GLX_CONTEXT_RESET_NOTIFICATION_STRATEGY_ARB: c_int = 0x8256;
+const GLX_LOSE_CONTEXT_ON_RESET_ARB: c_int = 0x8252;
+
+/// Defined by GLX_ARB_framebuffer_sRGB and GLX_EXT_framebuffer_sRGB
+const GLX_FRAMEBUFFER_SRGB_CAPABLE_ARB: c_int = 0x20B2;
+
+
+/// Set by [handle_glx_error] while GLX errors are intercepted
+static IS_GLX_ERROR_RECEIVED: AtomicBool = AtomicBool::new(false);
+
+
+
+impl OpenglCanvasTrait for OpenglCanvas {
+
+    fn new_shared(
+        client: &Self::ShellClient,
+        window_info: &WindowInfo,
+        canvas_info: &Self::CanvasInfo,
+        shared_canvas: &Self
+    )
+        -> Result<(Self::Window, Self)>
+    {
+        Self::new_with_shared_context(client, window_info, canvas_info, shared_canvas.context)
+    }
+
+
+    fn make_current(&self, client: &Self::ShellClient, window: &mut Self::Window) -> Result<()> {
+        use xcb::Xid;
+
+        let is_success = unsafe {
+            glx::glXMakeCurrent(client.xdisplay, window.handle.resource_id() as glx::GLXDrawable, self.context)
+        };
+
+        if is_success != 0 {
+            Ok(())
+        } else {
+            Err(Error::GraphicsApiFailed("cannot make GLX context current"))
+        }
+    }
+
+
+    fn swap_buffers(&self, client: &Self::ShellClient, window: &mut Self::Window) -> Result<()> {
+        use xcb::Xid;
+
+        self.assert_current()?;
+
+        unsafe {
+            glx::glXSwapBuffers(client.xdisplay, window.handle.resource_id() as glx::GLXDrawable);
+        }
+
+        client.finish_frame(window)
+    }
+
+
+    /// Adaptive vsync requires `GLX_EXT_swap_control_tear`
+    fn set_swap_interval(
+        &self,
+        client: &Self::ShellClient,
+        window: &mut Self::Window,
+        swap_interval: OpenglSwapInterval
+    )
+        -> Result<()>
+    {
+        use xcb::Xid;
+
+        self.assert_current()?;
+
+        let interval = match swap_interval {
+            OpenglSwapInterval::Immediate => 0,
+            OpenglSwapInterval::Vsync => 1,
+            OpenglSwapInterval::AdaptiveVsync => -1,
+        };
+
+        let is_ext_supported = Self::is_glx_extension_supported(client, "GLX_EXT_swap_control")
+            && (interval >= 0 || Self::is_glx_extension_supported(client, "GLX_EXT_swap_control_tear"));
+
+        let is_mesa_supported = interval >= 0 && Self::is_glx_extension_supported(client, "GLX_MESA_swap_control");
+
+        unsafe {
+            if is_ext_supported {
+                let swap_interval_ext = Self::load_gl_function::<GlxSwapIntervalExt>(c"glXSwapIntervalEXT")
+                    .ok_or(Error::GraphicsApiFailed("cannot load glXSwapIntervalEXT"))?;
+
+                let drawable = window.handle.resource_id() as glx::GLXDrawable;
+
+                Self::intercept_glx_errors(client, || swap_interval_ext(client.xdisplay, drawable, interval))
+                    .ok_or(Error::GraphicsApiFailed("cannot set swap interval"))
+            }
+            else if is_mesa_supported {
+                let swap_interval_mesa = Self::load_gl_function::<GlxSwapIntervalMesa>(c"glXSwapIntervalMESA")
+                    .ok_or(Error::GraphicsApiFailed("cannot load glXSwapIntervalMESA"))?;
+
+                match swap_interval_mesa(interval as c_uint) {
+                    0 => Ok(()),
+                    _ => Err(Error::GraphicsApiFailed("cannot set swap interval")),
+                }
+            }
+            else {
+                Err(Error::GraphicsApiFailed("swap interval is not supported"))
+            }
+        }
+    }
+
+
+    fn load_function(&self, client: &Self::ShellClient, window: &Self::Window, function_name: &str)
+        -> Result<*const c_void>
+    {
+        self.assert_current()?;
+
+        let function_name = CString::new(function_name).or(Err(Error::InvalidArgument))?;
+
+        match Self::get_proc_address(&function_name) {
+            Some(function) => Ok(function as *const c_void),
+            None => Err(Error::InvalidArgument),
+        }
+    }
+
+
+    /// Names starting with `GLX_` are looked up among the GLX extensions
+    fn is_extension_supported(&self, client: &Self::ShellClient, window: &Self::Window, extension_name: &str)
+        -> Result<bool>
+    {
+        self.assert_current()?;
+
+        if extension_name.starts_with("GLX_") {
+            return Ok(Self::is_glx_extension_supported(client, extension_name));
+        }
+
+        unsafe {
+            is_gl_extension_supported(
+                |function_name| Some(Self::get_proc_address(function_name)? as *const c_void),
+                extension_name
+            )
+        }
+    }
+
+}
+
+
+impl CanvasTrait for OpenglCanvas {
+
+    type ShellClient = ShellClient;
+    type Window = Window;
+
+    type CanvasInfo = OpenglInfo;
+
+    fn new(client: &Self::ShellClient, window_info: &WindowInfo, canvas_info: &Self::CanvasInfo)
+        -> Result<(Self::Window, Self)>
+    {
+        Self::new_with_shared_context(client, window_info, canvas_info, null_mut())
+    }
+
+
+    fn drop(self, client: &Self::ShellClient, window: Self::Window) -> Result<()> {
+        self.destroy_context(client);
+        client.drop_window(window)
+    }
+
+}
+
+
+impl OpenglCanvas {
+
+    /// `shared_context` may be null
+    fn new_with_shared_context(
+        client: &ShellClient,
+        window_info: &WindowInfo,
+        canvas_info: &OpenglInfo,
+        shared_context: glx::GLXContext
+    )
+        -> Result<(Window, Self)>
+    {
+        let (fbconfig, visual_info) = Self::choose_fbconfig(client, window_info, canvas_info)?;
+        let context = Self::create_context(client, fbconfig, canvas_info, shared_context)?;
+
+        let canvas = Self { context, fbconfig };
+
+        match client.new_window(window_info, &visual_info) {
+            Ok(window) => Ok((window, canvas)),
+            Err(error) => {
+                canvas.destroy_context(client);
+                Err(error)
+            }
+        }
+    }
+
+
+    fn get_fbconfig_attributes(client: &ShellClient, canvas_info: &OpenglInfo) -> Result<Vec<c_int>> {
+        let mut attributes = vec![
+            glx::GLX_X_RENDERABLE, 1,
+            glx::GLX_DRAWABLE_TYPE, glx::GLX_WINDOW_BIT,
+            glx::GLX_RENDER_TYPE, glx::GLX_RGBA_BIT,
+            glx::GLX_X_VISUAL_TYPE, glx::GLX_TRUE_COLOR,
+            glx::GLX_RED_SIZE, canvas_info.red_bits as c_int,
+            glx::GLX_GREEN_SIZE, canvas_info.green_bits as c_int,
+            glx::GLX_BLUE_SIZE, canvas_info.blue_bits as c_int,
+            glx::GLX_ALPHA_SIZE, canvas_info.alpha_bits as c_int,
+            glx::GLX_DEPTH_SIZE, canvas_info.depth_bits as c_int,
+            glx::GLX_STENCIL_SIZE, canvas_info.stencil_bits as c_int,
+            glx::GLX_DOUBLEBUFFER, (canvas_info.framebuffers >= 2) as c_int,
+        ];
+
+        if canvas_info.multisamples > 0 {
+            attributes.extend_from_slice(&[
+                glx::GLX_SAMPLE_BUFFERS, 1,
+                glx::GLX_SAMPLES, canvas_info.multisamples as c_int,
+            ]);
+        }
+
+        if canvas_info.srgb_flag {
+            let is_srgb_supported = Self::is_glx_extension_supported(client, "GLX_ARB_framebuffer_sRGB")
+                || Self::is_glx_extension_supported(client, "GLX_EXT_framebuffer_sRGB");
+
+            if !is_srgb_supported {
+                return Err(Error::GraphicsApiFailed("sRGB framebuffers are not supported by GLX"));
+            }
+
+            attributes.extend_from_slice(&[GLX_FRAMEBUFFER_SRGB_CAPABLE_ARB, 1]);
+        }
+
+        attributes.push(0);
+        Ok(attributes)
+    }
+
+
+    /// Transparent windows require a 32-bit visual, opaque windows prefer the visuals of the root depth so that the
+    /// compositor does not blend them
+    fn choose_fbconfig(client: &ShellClient, window_info: &WindowInfo, canvas_info: &OpenglInfo)
+        -> Result<(glx::GLXFBConfig, X11WindowVisualInfo)>
+    {
+        let attributes = Self::get_fbconfig_attributes(client, canvas_info)?;
+        let mut fbconfig_count: c_int = 0;
+
+        let fbconfigs = unsafe {
+            glx::glXChooseFBConfig(
+                client.xdisplay, client.default_screen_number, attributes.as_ptr(), &mut fbconfig_count
+            )
+        };
+
+        if fbconfigs.is_null() {
+            return Err(Error::GraphicsApiFailed("cannot find suitable GLX framebuffer configuration"));
+        }
+
+        let fbconfig_list = unsafe { std::slice::from_raw_parts(fbconfigs, fbconfig_count.max(0) as usize) };
+
+        let preferred_depth = if window_info.is_transparent {
+            32
+        } else {
+            client.get_default_screen().root_depth()
+        };
+
+        // The list is sorted from the best to the worst configuration
+        let chosen = fbconfig_list.iter()
+            .filter_map(|fbconfig| Some((*fbconfig, Self::get_fbconfig_visual_info(client, *fbconfig)?)))
+            .find(|(_, visual_info)| visual_info.color_depth == preferred_depth);
+
+        unsafe { xlib::XFree(fbconfigs as *mut c_void) };
+
+        chosen.ok_or(Error::GraphicsApiFailed("cannot find GLX framebuffer configuration for the window"))
+    }
+
+
+    fn get_fbconfig_visual_info(client: &ShellClient, fbconfig: glx::GLXFBConfig) -> Option<X11WindowVisualInfo> {
+        let visual = unsafe { glx::glXGetVisualFromFBConfig(client.xdisplay, fbconfig) };
+
+        if visual.is_null() {
+            return None;
+        }
+
+        let visualid = unsafe { (*visual).visualid as xcb::x::Visualid };
+        unsafe { xlib::XFree(visual as *mut c_void) };
+
+        client.get_visual_info(visualid)
+    }
+
+
+    /// `glXCreateContextAttribsARB` is required for OpenGL 3.0 and newer and for context flags
+    fn create_context(
+        client: &ShellClient,
+        fbconfig: glx::GLXFBConfig,
+        canvas_info: &OpenglInfo,
+        shared_context: glx::GLXContext
+    )
+        -> Result<glx::GLXContext>
+    {
+        let create_context_attribs = Self::is_glx_extension_supported(client, "GLX_ARB_create_context")
+            .then(|| unsafe {
+                Self::load_gl_function::<GlxCreateContextAttribsArb>(c"glXCreateContextAttribsARB")
+            })
+            .flatten();
+
+        let context = match create_context_attribs {
+            Some(create_context_attribs) => {
+                let profile = match canvas_info.api {
+                    OpenglApi::Opengl if canvas_info.compatibility_flag => {
+                        glx::arb::GLX_CONTEXT_COMPATIBILITY_PROFILE_BIT_ARB
+                    }
+
+                    OpenglApi::Opengl => glx::arb::GLX_CONTEXT_CORE_PROFILE_BIT_ARB,
+
+                    OpenglApi::OpenglEs => {
+                        if !Self::is_glx_extension_supported(client, "GLX_EXT_create_context_es2_profile") {
+                            return Err(Error::GraphicsApiFailed("OpenGL ES is not supported by GLX"));
+                        }
+
+                        GLX_CONTEXT_ES2_PROFILE_BIT_EXT
+                    }
+                };
+
+                let mut attributes = vec![
+                    glx::arb::GLX_CONTEXT_MAJOR_VERSION_ARB, canvas_info.major_version as c_int,
+                    glx::arb::GLX_CONTEXT_MINOR_VERSION_ARB, canvas_info.minor_version as c_int,
+                    glx::arb::GLX_CONTEXT_PROFILE_MASK_ARB, profile,
+                ];
+
+                let mut flags = 0;
+
+                if canvas_info.debug_flag {
+                    flags |= glx::arb::GLX_CONTEXT_DEBUG_BIT_ARB;
+                }
+
+                if canvas_info.robustness_flag {
+                    if !Self::is_glx_extension_supported(client, "GLX_ARB_create_context_robustness") {
+                        return Err(Error::GraphicsApiFailed("robust contexts are not supported by GLX"));
+                    }
+
+                    flags |= GLX_CONTEXT_ROBUST_ACCESS_BIT_ARB;
+
+                    attributes.extend_from_slice(&[
+                        GLX_CONTEXT_RESET_NOTIFICATION_STRATEGY_ARB, GLX_LOSE_CONTEXT_ON_RESET_ARB
+                    ]);
+                }
+
+                attributes.extend_from_slice(&[glx::arb::GLX_CONTEXT_FLAGS_ARB, flags, 0]);
+
+                Self::intercept_glx_errors(client, || unsafe {
+                    create_context_attribs(client.xdisplay, fbconfig, shared_context, 1, attributes.as_ptr())
+                })
+            }
+
+            None if Self::is_legacy_context_sufficient(canvas_info) => {
+                Self::intercept_glx_errors(client, || unsafe {
+                    glx::glXCreateNewContext(client.xdisplay, fbconfig, glx::GLX_RGBA_TYPE, shared_context, 1)
+                })
+            }
+
+            None => return Err(Error::GraphicsApiFailed("GLX_ARB_create_context is not supported")),
+        };
+
+        match context {
+            Some(context) if !context.is_null() => Ok(context),
+            _ => Err(Error::GraphicsApiFailed("cannot create GLX context")),
+        }
+    }
+
+
+    /// Contexts created without `GLX_ARB_create_context` support neither new versions nor flags
+    fn is_legacy_context_sufficient(canvas_info: &OpenglInfo) -> bool {
+        canvas_info.api == OpenglApi::Opengl
+            && canvas_info.major_version < 3
+            && !canvas_info.debug_flag
+            && !canvas_info.robustness_flag
+    }
+
+
+    /// Xlib terminates the process on errors by default, which is not acceptable for requests that fail if
+    /// the requested configuration is not supported.
+    ///
+    /// Returns `None` if an error has occurred.
+    fn intercept_glx_errors<T>(client: &ShellClient, function: impl FnOnce() -> T) -> Option<T> {
+        IS_GLX_ERROR_RECEIVED.store(false, Ordering::SeqCst);
+
+        let result = unsafe {
+            let previous_handler = xlib::XSetErrorHandler(Some(handle_glx_error));
+            let result = function();
+            xlib::XSync(client.xdisplay, 0);
+            xlib::XSetErrorHandler(previous_handler);
+            result
+        };
+
+        if IS_GLX_ERROR_RECEIVED.load(Ordering::SeqCst) {
+            None
+        } else {
+            Some(result)
+        }
+    }
+
+
+    fn destroy_context(&self, client: &ShellClient) {
+        unsafe {
+            if glx::glXGetCurrentContext() == self.context {
+                glx::glXMakeCurrent(client.xdisplay, 0, null_mut());
+            }
+
+            glx::glXDestroyContext(client.xdisplay, self.context);
+        }
+    }
+
+
+    fn assert_current(&self) -> Result<()> {
+        if unsafe { glx::glXGetCurrentContext() } == self.context {
+            Ok(())
+        } else {
+            Err(Error::GraphicsApiFailed("GLX context is not current"))
+        }
+    }
+
+
+    fn get_proc_address(function_name: &CStr) -> Option<unsafe extern "C" fn()> {
+        unsafe { glx::glXGetProcAddressARB(function_name.as_ptr() as *const u8) }
+    }
+
+
+    /// `FunctionT` must be an `extern "C"` function pointer type
+    unsafe fn load_gl_function<FunctionT: Copy>(function_name: &CStr) -> Option<FunctionT> {
+        let function = Self::get_proc_address(function_name)?;
+        Some(std::mem::transmute_copy(&function))
+    }
+
+
+    fn is_glx_extension_supported(client: &ShellClient, extension_name: &str) -> bool {
+        let extensions = unsafe { glx::glXQueryExtensionsString(client.xdisplay, client.default_screen_number) };
+
+        if extensions.is_null() {
+            return false;
+        }
+
+        is_name_in_list(unsafe { CStr::from_ptr(extensions) }, extension_name)
+    }
+
+}
+
+
+unsafe extern "C" fn handle_glx_error(_display: *mut xlib::Display, _event: *mut xlib::XErrorEvent) -> c_int {
+    IS_GLX_ERROR_RECEIVED.store(true, Ordering::SeqCst);
+    0
+}
@@ -1,16 +1,583 @@
+pub use crate::core::interface::rawpix::*;
+
 use crate::{
     *,
-    errors::Result,
+    errors::*,
 };
 
+use std::ops::{Deref, DerefMut};
+
+
+/// Software canvas. Back buffers are allocated in MIT-SHM segments if the server supports it and is reachable
+/// via shared memory, otherwise the pixels are transferred with plain PutImage requests.
+pub struct RawpixCanvas {
+    graphics_context: xcb::x::Gcontext,
+    color_depth: u8,
+    format: PixelFormat,
+
+    /// Rows of images are padded to a multiple of this number of bytes
+    scanline_pad: usize,
+
+    /// Back buffers indexed by their identifiers
+    buffers: Vec<X11PixelBuffer>,
+
+    /// Number of frames presented so far
+    frame_count: u64,
+
+    /// True if allocating shared memory has failed once, so it is not tried again
+    is_shm_broken: bool,
+}
+
+
+struct X11PixelBuffer {
+    size: PixelSize,
+    padding: usize,
+
+    /// `None` while the buffer is acquired by the application
+    memory: Option<PixelMemory>,
+
+    /// Address and length of the memory, which identify the buffer when the application returns it.
+    /// The address is stored as a number, so the canvas stays `Send`.
+    address: usize,
+    length: usize,
+
+    /// Present if the buffer is attached to the server as a shared memory segment
+    shm_segment: Option<xcb::shm::Seg>,
+
+    /// Number of the frame that was presented from this buffer last time. `None` if the contents are undefined.
+    presented_frame: Option<u64>,
+}
+
+
+/// Memory of a System V shared memory segment mapped into the address space of the process
+struct X11SharedMemory {
+    shmid: i32,
+    address: *mut u8,
+    length: usize,
+}
+
+
+
+impl RawpixCanvasTrait for RawpixCanvas {
+
+    /// Formats with alpha are supported by transparent windows only
+    fn supported_formats(client: &Self::ShellClient) -> Vec<PixelFormat> {
+        let visuals = [Some(client.get_default_visual_info()), client.get_transparent_visual_info()];
+
+        visuals.iter()
+            .flatten()
+            .filter_map(|visual_info| Self::get_visual_layout(client, visual_info))
+            .map(|(format, _)| format)
+            .collect()
+    }
+
+
+    fn acquire_buffer(&mut self, client: &Self::ShellClient, window: &Self::Window) -> Result<RawpixData> {
+        let size = window.state.size.clone();
+
+        // The idle buffer with the most recent frame has the most reusable contents
+        let idle_buffer_id = self.buffers.iter()
+            .enumerate()
+            .filter(|(_, buffer)| buffer.memory.is_some())
+            .max_by_key(|(_, buffer)| (buffer.size == size, buffer.presented_frame))
+            .map(|(id, _)| id);
+
+        let id = match idle_buffer_id {
+            Some(id) => id,
+            None => {
+                self.buffers.push(X11PixelBuffer::new_plain(PixelSize::new(0, 0), 0, self.format));
+                self.buffers.len() - 1
+            }
+        };
+
+        if self.buffers[id].size != size {
+            self.reallocate_buffer(client, id, size)?;
+        }
+
+        let buffer = &mut self.buffers[id];
+
+        let age = match buffer.presented_frame {
+            Some(frame) => (self.frame_count - frame).min(u32::MAX as u64) as u32,
+            None => 0,
+        };
+
+        Ok(RawpixData::new(
+            id, self.format, buffer.size.clone(), buffer.padding, age, buffer.memory.take().unwrap()
+        ))
+    }
+
+
+    fn release_buffer(&mut self, client: &Self::ShellClient, buffer: RawpixData) -> Result<()> {
+        self.return_buffer(buffer)
+    }
+
+
+    fn present(&mut self, client: &Self::ShellClient, window: &mut Self::Window, buffer: RawpixData) -> Result<()> {
+        let size = buffer.get_size();
+        let rect = PixelRect::new(0, 0, size.width, size.height);
+        self.present_region(client, window, buffer, &[rect])
+    }
+
+
+    fn present_region(
+        &mut self, client: &Self::ShellClient, window: &mut Self::Window, buffer: RawpixData, rects: &[PixelRect]
+    )
+        -> Result<()>
+    {
+        let id = buffer.get_id();
+        self.return_buffer(buffer)?;
+
+        let buffer = &self.buffers[id];
+        let rects: Vec<PixelRect> = rects.iter().filter_map(|rect| buffer.clip_rect(rect)).collect();
+
+        match buffer.shm_segment {
+            Some(shm_segment) => self.put_shm_image(client, window.handle, buffer, shm_segment, &rects)?,
+            None => self.put_image(client, window.handle, buffer, &rects)?,
+        }
+
+        self.buffers[id].presented_frame = Some(self.frame_count);
+        self.frame_count += 1;
+
+        client.finish_frame(window)
+    }
+
+}
+
+
+impl CanvasTrait for RawpixCanvas {
+
+    type ShellClient = ShellClient;
+    type Window = Window;
+
+    type CanvasInfo = RawpixInfo;
+
+    fn new(client: &Self::ShellClient, window_info: &WindowInfo, canvas_info: &Self::CanvasInfo)
+        -> Result<(Self::Window, Self)>
+    {
+        let visual_info = client.get_window_visual_info(window_info)?;
+
+        let (format, scanline_pad) = Self::get_visual_layout(client, &visual_info)
+            .ok_or(Error::PixelFormatNotSupported)?;
+
+        if canvas_info.format.is_some_and(|requested_format| requested_format != format) {
+            return Err(Error::PixelFormatNotSupported);
+        }
+
+        let window = client.new_window(window_info, &visual_info)?;
+
+        let graphics_context = match Self::create_graphics_context(client, window.handle) {
+            Ok(graphics_context) => graphics_context,
+            Err(error) => {
+                client.drop_window(window)?;
+                return Err(error);
+            }
+        };
+
+        let canvas = Self {
+            graphics_context,
+            color_depth: visual_info.color_depth,
+            format,
+            scanline_pad,
+            buffers: Vec::new(),
+            frame_count: 0,
+            is_shm_broken: !Self::is_shm_available(client),
+        };
+
+        Ok((window, canvas))
+    }
+
+
+    fn drop(mut self, client: &Self::ShellClient, window: Self::Window) -> Result<()> {
+        for buffer in &mut self.buffers {
+            buffer.detach(client)?;
+        }
+
+        client.connection.send_and_check_request(&xcb::x::FreeGc {
+            gc: self.graphics_context,
+        })
+        .or_else(|_| Err(Error::PlatformApiFailed("cannot free graphics context")))?;
+
+        client.drop_window(window)
+    }
+
+}
+
+
+impl RawpixCanvas {
+
+    /// Returns the pixel format of the visual and the scanline pad in bytes.
+    /// 
+    /// Returns `None` if the visual does not correspond to any [PixelFormat].
+    fn get_visual_layout(client: &ShellClient, visual_info: &X11WindowVisualInfo) -> Option<(PixelFormat, usize)> {
+        let setup = client.connection.get_setup();
+
+        let visual = client.get_default_screen().allowed_depths()
+            .flat_map(|depth| depth.visuals())
+            .find(|visual| visual.visual_id() == visual_info.visualid)?;
+
+        let pixmap_format = setup.pixmap_formats().iter()
+            .find(|format| format.depth() == visual_info.color_depth)?;
+
+        let is_lsb_first = setup.image_byte_order() == xcb::x::ImageOrder::LsbFirst;
+
+        if visual.green_mask() != 0x00_FF_00 {
+            return None;
+        }
+
+        let is_red_high = match (visual.red_mask(), visual.blue_mask()) {
+            (0xFF_00_00, 0x00_00_FF) => true,
+            (0x00_00_FF, 0xFF_00_00) => false,
+            _ => return None,
+        };
+
+        // The unused byte of 32-bit visuals is alpha
+        let has_alpha = visual_info.color_depth == 32;
+
+        // Pixels are stored as integers, formats list components in memory order
+        let format = match (pixmap_format.bits_per_pixel(), is_lsb_first, is_red_high) {
+            (32, true, true) if has_alpha => PixelFormat::BGRA_8888,
+            (32, true, false) if has_alpha => PixelFormat::RGBA_8888,
+            (32, false, true) if has_alpha => PixelFormat::ARGB_8888,
+            (32, false, false) if has_alpha => PixelFormat::ABGR_8888,
+            (32, true, true) => PixelFormat::BGRX_8888,
+            (32, true, false) => PixelFormat::RGBX_8888,
+            (32, false, true) => PixelFormat::XRGB_8888,
+            (32, false, false) => PixelFormat::XBGR_8888,
+            (24, true, true) | (24, false, false) => PixelFormat::BGR_888,
+            (24, true, false) | (24, false, true) => PixelFormat::RGB_888,
+            _ => return None,
+        };
+
+        Some((format, pixmap_format.scanline_pad() as usize / 8))
+    }
+
+
+    fn get_padding(&self, width: u32) -> usize {
+        let row_length = width as usize * self.format.size_of_pixel();
+        (self.scanline_pad - row_length % self.scanline_pad) % self.scanline_pad
+    }
+
+
+    fn create_graphics_context(client: &ShellClient, window_handle: X11WindowHandle) -> Result<xcb::x::Gcontext> {
+        let graphics_context = client.connection.generate_id();
+
+        client.connection.send_and_check_request(&xcb::x::CreateGc {
+            cid: graphics_context,
+            drawable: xcb::x::Drawable::Window(window_handle),
+            value_list: &[
+                xcb::x::Gc::GraphicsExposures(false),
+            ]
+        })
+        .or_else(|_| Err(Error::PlatformApiFailed("cannot create graphics context")))?;
+
+        Ok(graphics_context)
+    }
 
-pub(crate) struct CanvasData {
-    graphics_context: u32,
-    
-    /// Back buffer
-    rawpix_data: core::interface::rawpix::RawpixData,
 
-    /// Front buffer
-    pixmap: u32,
+    fn is_shm_available(client: &ShellClient) -> bool {
+        let is_extension_active = client.connection.active_extensions()
+            .any(|extension| extension == xcb::Extension::Shm);
+
+        if !is_extension_active {
+            return false;
+        }
+
+        let cookie = client.connection.send_request(&xcb::shm::QueryVersion {});
+        client.connection.wait_for_reply(cookie).is_ok()
+    }
+
+
+    /// Buffers acquired by the application keep their memory, only the server side is detached
+    fn reallocate_buffer(&mut self, client: &ShellClient, id: usize, size: PixelSize) -> Result<()> {
+        self.buffers[id].detach(client)?;
+
+        let padding = self.get_padding(size.width);
+
+        if !self.is_shm_broken {
+            // Remote clients cannot attach shared memory, the plain buffer is used then
+            match X11PixelBuffer::new_shared(client, size.clone(), padding, self.format) {
+                Ok(buffer) => {
+                    self.buffers[id] = buffer;
+                    return Ok(());
+                }
+
+                Err(_) => self.is_shm_broken = true,
+            }
+        }
+
+        self.buffers[id] = X11PixelBuffer::new_plain(size, padding, self.format);
+        Ok(())
+    }
+
+
+    /// Fails with [Error::InvalidArgument] if the buffer has not been acquired from this canvas
+    fn return_buffer(&mut self, buffer: RawpixData) -> Result<()> {
+        let format = self.format;
+
+        let slot = self.buffers.get_mut(buffer.get_id())
+            .filter(|slot| slot.memory.is_none())
+            .ok_or(Error::InvalidArgument)?;
+
+        // Buffers constructed by the application may reuse the identifier of an acquired buffer
+        let is_genuine = buffer.get_format() == format
+            && buffer.get_size() == slot.size
+            && buffer.get_padding() == slot.padding
+            && buffer.get_pixels().as_ptr() as usize == slot.address
+            && buffer.get_pixels().len() == slot.length;
+
+        if !is_genuine {
+            return Err(Error::InvalidArgument);
+        }
+
+        slot.memory = Some(buffer.into_pixels());
+        Ok(())
+    }
+
+
+    fn put_shm_image(
+        &self,
+        client: &ShellClient,
+        window_handle: X11WindowHandle,
+        buffer: &X11PixelBuffer,
+        shm_segment: xcb::shm::Seg,
+        rects: &[PixelRect]
+    )
+        -> Result<()>
+    {
+        let cookies: Vec<_> = rects.iter()
+            .map(|rect| client.connection.send_request_checked(&xcb::shm::PutImage {
+                drawable: xcb::x::Drawable::Window(window_handle),
+                gc: self.graphics_context,
+                total_width: buffer.size.width as u16,
+                total_height: buffer.size.height as u16,
+                src_x: rect.x as u16,
+                src_y: rect.y as u16,
+                src_width: rect.width as u16,
+                src_height: rect.height as u16,
+                dst_x: rect.x as i16,
+                dst_y: rect.y as i16,
+                depth: self.color_depth,
+                format: xcb::x::ImageFormat::ZPixmap as u8,
+                send_event: false,
+                shmseg: shm_segment,
+                offset: 0,
+            }))
+            .collect();
+
+        // Checking the requests makes sure that the server has copied the pixels before the buffer is modified again
+        for cookie in cookies {
+            client.connection.check_request(cookie)
+                .or_else(|_| Err(Error::PlatformApiFailed("cannot put shared memory image")))?;
+        }
+
+        Ok(())
+    }
+
+
+    /// Splits the rectangles into bands of rows that fit into the maximum request length
+    fn put_image(
+        &self, client: &ShellClient, window_handle: X11WindowHandle, buffer: &X11PixelBuffer, rects: &[PixelRect]
+    )
+        -> Result<()>
+    {
+        let pixel_size = self.format.size_of_pixel();
+        let stride = buffer.get_stride(self.format);
+
+        // The maximum request length is measured in 4-byte units, the request header takes 24 bytes
+        let max_data_length = client.connection.get_maximum_request_length() as usize * 4 - 24;
+
+        let pixels: &[u8] = buffer.memory.as_ref().unwrap();
+        let mut band = Vec::new();
+
+        for rect in rects {
+            let (x, y, width) = (rect.x as usize, rect.y as usize, rect.width as usize);
+
+            // Rows sent to the server must be padded like the rows of the buffer
+            let row_length = width * pixel_size;
+            let band_stride = row_length + self.get_padding(rect.width);
+            let rows_per_request = (max_data_length / band_stride).max(1);
+
+            for first_row in (y .. y + rect.height as usize).step_by(rows_per_request) {
+                let row_count = rows_per_request.min(y + rect.height as usize - first_row);
+
+                let data = if x == 0 && band_stride == stride {
+                    &pixels[first_row * stride .. (first_row + row_count) * stride]
+                } else {
+                    band.clear();
+                    band.resize(band_stride * row_count, 0);
+
+                    for row in 0..row_count {
+                        let start = (first_row + row) * stride + x * pixel_size;
+                        band[row * band_stride .. row * band_stride + row_length]
+                            .copy_from_slice(&pixels[start .. start + row_length]);
+                    }
+
+                    &band[..]
+                };
+
+                client.connection.send_request(&xcb::x::PutImage {
+                    format: xcb::x::ImageFormat::ZPixmap,
+                    drawable: xcb::x::Drawable::Window(window_handle),
+                    gc: self.graphics_context,
+                    width: rect.width as u16,
+                    height: row_count as u16,
+                    dst_x: rect.x as i16,
+                    dst_y: first_row as i16,
+                    left_pad: 0,
+                    depth: self.color_depth,
+                    data,
+                });
+            }
+        }
+
+        client.connection.flush()
+            .or_else(|_| Err(Error::PlatformApiFailed("cannot put image")))
+    }
+
 }
 
+
+impl X11PixelBuffer {
+
+    fn new_plain(size: PixelSize, padding: usize, format: PixelFormat) -> Self {
+        let length = Self::get_length(&size, padding, format);
+        let memory: PixelMemory = Box::new(vec![0u8; length].into_boxed_slice());
+
+        Self {
+            size,
+            padding,
+            address: memory.as_ptr() as usize,
+            length: memory.len(),
+            memory: Some(memory),
+            shm_segment: None,
+            presented_frame: None,
+        }
+    }
+
+
+    fn new_shared(client: &ShellClient, size: PixelSize, padding: usize, format: PixelFormat) -> Result<Self> {
+        let length = Self::get_length(&size, padding, format);
+        let memory = X11SharedMemory::new(length.max(1))?;
+
+        let shm_segment = client.connection.generate_id();
+
+        let attach_result = client.connection.send_and_check_request(&xcb::shm::Attach {
+            shmseg: shm_segment,
+            shmid: memory.shmid as u32,
+            read_only: true,
+        });
+
+        memory.mark_for_removal();
+
+        attach_result.or_else(|_| Err(Error::PlatformApiFailed("cannot attach shared memory")))?;
+
+        Ok(Self {
+            size,
+            padding,
+            address: memory.as_ptr() as usize,
+            length: memory.len(),
+            memory: Some(Box::new(memory)),
+            shm_segment: Some(shm_segment),
+            presented_frame: None,
+        })
+    }
+
+
+    fn get_length(size: &PixelSize, padding: usize, format: PixelFormat) -> usize {
+        (size.width as usize * format.size_of_pixel() + padding) * size.height as usize
+    }
+
+
+    fn get_stride(&self, format: PixelFormat) -> usize {
+        self.size.width as usize * format.size_of_pixel() + self.padding
+    }
+
+
+    /// Returns `None` if the rectangle does not intersect the buffer.
+    /// 
+    /// The server clips the image if the window has been resized after the buffer was requested.
+    fn clip_rect(&self, rect: &PixelRect) -> Option<PixelRect> {
+        let width = self.size.width.min(u16::MAX as u32) as i64;
+        let height = self.size.height.min(u16::MAX as u32) as i64;
+
+        let left = (rect.x as i64).clamp(0, width);
+        let top = (rect.y as i64).clamp(0, height);
+        let right = (rect.x as i64 + rect.width as i64).clamp(0, width);
+        let bottom = (rect.y as i64 + rect.height as i64).clamp(0, height);
+
+        if left < right && top < bottom {
+            Some(PixelRect::new(left as i32, top as i32, (right - left) as u32, (bottom - top) as u32))
+        } else {
+            None
+        }
+    }
+
+
+    /// Detaches the shared memory from the server. The memory stays valid in the client until it is dropped.
+    fn detach(&mut self, client: &ShellClient) -> Result<()> {
+        if let Some(shm_segment) = self.shm_segment.take() {
+            client.connection.send_and_check_request(&xcb::shm::Detach {
+                shmseg: shm_segment,
+            })
+            .or_else(|_| Err(Error::PlatformApiFailed("cannot detach shared memory")))?;
+        }
+
+        Ok(())
+    }
+
+}
+
+
+impl X11SharedMemory {
+
+    fn new(length: usize) -> Result<Self> {
+        let shmid = unsafe { libc::shmget(libc::IPC_PRIVATE, length, libc::IPC_CREAT | 0o600) };
+
+        if shmid == -1 {
+            return Err(Error::PlatformApiFailed("cannot create shared memory"));
+        }
+
+        let address = unsafe { libc::shmat(shmid, null(), 0) };
+
+        if address as isize == -1 {
+            unsafe { libc::shmctl(shmid, libc::IPC_RMID, null_mut()) };
+            return Err(Error::PlatformApiFailed("cannot map shared memory"));
+        }
+
+        Ok(Self { shmid, address: address as *mut u8, length })
+    }
+
+
+    /// The segment is destroyed as soon as both the client and the server detach it
+    fn mark_for_removal(&self) {
+        unsafe { libc::shmctl(self.shmid, libc::IPC_RMID, null_mut()) };
+    }
+
+}
+
+
+// The memory is mapped into the whole process, so it can be accessed from any thread
+unsafe impl Send for X11SharedMemory {}
+
+
+impl Deref for X11SharedMemory {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.address, self.length) }
+    }
+}
+
+
+impl DerefMut for X11SharedMemory {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.address, self.length) }
+    }
+}
+
+
+impl Drop for X11SharedMemory {
+    fn drop(&mut self) {
+        unsafe { libc::shmdt(self.address as *const libc::c_void) };
+    }
+}
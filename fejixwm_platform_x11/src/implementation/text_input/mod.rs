@@ -0,0 +1,18 @@
+use crate::{
+    *,
+    errors::*
+};
+
+pub use crate::core::interface::text_input::*;
+
+
+impl TextInputController for ShellClient {
+
+    fn set_text_cursor_area(&self, window: &mut Self::Window, area: PixelRect) -> Result<()> {
+        match &window.text_input {
+            Some(text_input) => text_input.set_cursor_area(&area),
+            None => Ok(()),
+        }
+    }
+
+}
@@ -0,0 +1,98 @@
+pub use crate::core::interface::vulkan::*;
+
+use crate::{
+    *,
+    errors::*,
+};
+
+use std::{
+    ffi::{c_void, CStr},
+    os::raw::c_int,
+};
+
+
+/// Vulkan canvas based on `VK_KHR_xcb_surface`. Works with software drivers such as lavapipe.
+pub struct VulkanCanvas;
+
+
+/// `VkXcbSurfaceCreateInfoKHR`
+#[repr(C)]
+struct VkXcbSurfaceCreateInfoKhr {
+    s_type: c_int,
+    p_next: *const c_void,
+    flags: u32,
+    connection: *mut xcb::ffi::xcb_connection_t,
+    window: u32,
+}
+
+type VkCreateXcbSurfaceKhr = unsafe extern "system" fn(
+    VulkanInstance, *const VkXcbSurfaceCreateInfoKhr, VulkanAllocationCallbacks, *mut VulkanSurface
+) -> c_int;
+
+const VK_STRUCTURE_TYPE_XCB_SURFACE_CREATE_INFO_KHR: c_int = 1000005000;
+const VK_SUCCESS: c_int = 0;
+
+
+
+impl VulkanCanvasTrait for VulkanCanvas {
+
+    fn required_instance_extensions(client: &Self::ShellClient) -> Vec<&'static CStr> {
+        vec![c"VK_KHR_surface", c"VK_KHR_xcb_surface"]
+    }
+
+
+    unsafe fn create_surface(
+        &self,
+        client: &Self::ShellClient,
+        window: &Self::Window,
+        instance: VulkanInstance,
+        get_instance_proc_addr: VulkanGetInstanceProcAddr,
+        allocator: VulkanAllocationCallbacks
+    )
+        -> Result<VulkanSurface>
+    {
+        use xcb::Xid;
+
+        let create_xcb_surface: VkCreateXcbSurfaceKhr = std::mem::transmute(
+            get_instance_proc_addr(instance, c"vkCreateXcbSurfaceKHR".as_ptr())
+                .ok_or(Error::GraphicsApiFailed("VK_KHR_xcb_surface is not enabled"))?
+        );
+
+        let create_info = VkXcbSurfaceCreateInfoKhr {
+            s_type: VK_STRUCTURE_TYPE_XCB_SURFACE_CREATE_INFO_KHR,
+            p_next: null(),
+            flags: 0,
+            connection: client.connection.get_raw_conn(),
+            window: window.handle.resource_id(),
+        };
+
+        let mut surface: VulkanSurface = 0;
+
+        match create_xcb_surface(instance, &create_info, allocator, &mut surface) {
+            VK_SUCCESS => Ok(surface),
+            _ => Err(Error::GraphicsApiFailed("cannot create Vulkan surface")),
+        }
+    }
+
+}
+
+
+impl CanvasTrait for VulkanCanvas {
+
+    type ShellClient = ShellClient;
+    type Window = Window;
+
+    type CanvasInfo = ();
+
+    fn new(client: &Self::ShellClient, window_info: &WindowInfo, canvas_info: &Self::CanvasInfo)
+        -> Result<(Self::Window, Self)>
+    {
+        let window = client.new_window(window_info, &client.get_window_visual_info(window_info)?)?;
+        Ok((window, Self))
+    }
+
+    fn drop(self, client: &Self::ShellClient, window: Self::Window) -> Result<()> {
+        client.drop_window(window)
+    }
+
+}
@@ -1,6 +1,7 @@
 use crate::{
     *,
-    errors::*
+    errors::*,
+    implementation::xembed::*,
 };
 
 pub use crate::core::interface::window_manip::*;
@@ -9,6 +10,16 @@ pub use crate::core::interface::window_manip::*;
 impl VisibilityController for ShellClient {
 
     fn set_visible(&self, window: &mut Self::Window, visible: bool) -> Result<()> {
+        // The embedder maps and unmaps embedded windows
+        if let Some(xembed_client) = &mut window.xembed_client {
+            xembed_client.flags = if visible { XEMBED_MAPPED } else { 0 };
+            self.set_xembed_info(window.handle, xembed_client.flags)?;
+
+            if xembed_client.embedder.is_some() {
+                return Ok(());
+            }
+        }
+
         if visible {
             self.connection.send_and_check_request(&xcb::x::MapWindow {
                 window: window.handle
@@ -43,4 +54,95 @@ impl TitleController for ShellClient {
         Ok(())
     }
 
-}
\ No newline at end of file
+}
+
+
+impl FocusController for ShellClient {
+
+    fn request_focus(&self, window: &mut Self::Window) -> Result<()> {
+        if let Some(embedder) = window.xembed_client.as_ref().and_then(|xembed_client| xembed_client.embedder) {
+            return self.send_xembed_message(embedder, XEMBED_REQUEST_FOCUS, 0, 0, 0);
+        }
+
+        // Source indication 1 means that the request comes from a normal application
+        let event = xcb::x::ClientMessageEvent::new(
+            window.handle,
+            self.atoms._NET_ACTIVE_WINDOW,
+            xcb::x::ClientMessageData::Data32([1, self.last_user_time.get(), 0, 0, 0])
+        );
+
+        self.connection.send_and_check_request(&xcb::x::SendEvent {
+            propagate: false,
+            destination: xcb::x::SendEventDest::Window(self.get_default_window()),
+            event_mask: xcb::x::EventMask::SUBSTRUCTURE_NOTIFY | xcb::x::EventMask::SUBSTRUCTURE_REDIRECT,
+            event: &event
+        })
+        .or_else(|_| Err(Error::PlatformApiFailed("cannot request focus")))?;
+
+        Ok(())
+    }
+
+}
+
+
+impl SizeConstraintsController for ShellClient {
+
+    fn set_size_constraints(&self, window: &mut Self::Window, constraints: &SizeConstraints) -> Result<()> {
+        window.size_constraints = constraints.clone();
+        self.update_window_size_hints(window)
+    }
+
+
+    fn set_resizable(&self, window: &mut Self::Window, is_resizable: bool) -> Result<()> {
+        window.is_resizable = is_resizable;
+        self.update_window_size_hints(window)
+    }
+
+}
+
+
+impl ResizeController for ShellClient {
+
+    fn set_size(&self, window: &mut Self::Window, size: PixelSize) -> Result<()> {
+        // Requesting the current size changes nothing, so no ConfigureNotify has to clear the pending size
+        window.pending_size = (size != window.state.size).then(|| size.clone());
+
+        // Window managers keep non-resizable windows within the size hints
+        if !window.is_resizable {
+            self.update_window_size_hints(window)?;
+        }
+
+        self.connection.send_and_check_request(&xcb::x::ConfigureWindow {
+            window: window.handle,
+            value_list: &[
+                xcb::x::ConfigWindow::Width(size.width),
+                xcb::x::ConfigWindow::Height(size.height),
+            ]
+        })
+        .or_else(|_| Err(Error::PlatformApiFailed("cannot resize window")))
+    }
+
+}
+
+
+impl MoveController for ShellClient {
+
+    fn set_position(&self, window: &mut Self::Window, position: PixelPosition) -> Result<()> {
+        let is_first_request = window.requested_position.is_none();
+        window.requested_position = Some(position);
+
+        if is_first_request {
+            self.update_window_size_hints(window)?;
+        }
+
+        self.connection.send_and_check_request(&xcb::x::ConfigureWindow {
+            window: window.handle,
+            value_list: &[
+                xcb::x::ConfigWindow::X(position.x),
+                xcb::x::ConfigWindow::Y(position.y),
+            ]
+        })
+        .or_else(|_| Err(Error::PlatformApiFailed("cannot move window")))
+    }
+
+}
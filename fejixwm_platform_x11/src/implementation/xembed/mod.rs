@@ -0,0 +1,300 @@
+//! [XEmbed](https://specifications.freedesktop.org/xembed-spec/) lets a window of one application (the embedder)
+//! host a window of another application (the client), as used by audio plugin hosts and system trays.
+
+use crate::{
+    *,
+    errors::*
+};
+
+
+/// Embedder side of the XEmbed protocol
+pub trait XEmbedEmbedderController {
+
+    /// Reparents the foreign `client` window into `window`, resizes it to fill `window` and maps it when
+    /// the client asks for it. Keyboard focus and key events of `window` are forwarded to the client.
+    ///
+    /// A window hosts at most one client, the previous one is released.
+    fn embed_client(&self, window: &mut Window, client: xcb::x::Window) -> Result<()>;
+
+    /// Moves the client back to the root window and stops forwarding events to it.
+    /// Does nothing if no client is embedded.
+    fn release_client(&self, window: &mut Window) -> Result<()>;
+
+    /// Returns `None` if no client is embedded or the client has been destroyed
+    fn get_embedded_client(&self, window: &Window) -> Option<xcb::x::Window>;
+
+}
+
+
+/// Client side of the XEmbed protocol
+pub trait XEmbedClientController {
+
+    /// Announces that the window can be embedded. Its XID can then be passed to a foreign embedder.
+    ///
+    /// While the window is embedded, `VisibilityController` asks the embedder to map or unmap it and
+    /// `FocusController` asks the embedder for focus.
+    fn enable_xembed(&self, window: &mut Window) -> Result<()>;
+
+    /// Returns `None` if the window is not embedded
+    fn get_embedder(&self, window: &Window) -> Option<xcb::x::Window>;
+
+}
+
+
+pub(crate) const XEMBED_PROTOCOL_VERSION: u32 = 0;
+
+/// Flag of _XEMBED_INFO, set if the client wants to be mapped
+pub(crate) const XEMBED_MAPPED: u32 = 1 << 0;
+
+pub(crate) const XEMBED_EMBEDDED_NOTIFY: u32 = 0;
+pub(crate) const XEMBED_WINDOW_ACTIVATE: u32 = 1;
+pub(crate) const XEMBED_WINDOW_DEACTIVATE: u32 = 2;
+pub(crate) const XEMBED_REQUEST_FOCUS: u32 = 3;
+pub(crate) const XEMBED_FOCUS_IN: u32 = 4;
+pub(crate) const XEMBED_FOCUS_OUT: u32 = 5;
+pub(crate) const XEMBED_FOCUS_NEXT: u32 = 6;
+pub(crate) const XEMBED_FOCUS_PREV: u32 = 7;
+pub(crate) const XEMBED_MODALITY_ON: u32 = 10;
+pub(crate) const XEMBED_MODALITY_OFF: u32 = 11;
+
+/// Details of [XEMBED_FOCUS_IN]
+pub(crate) const XEMBED_FOCUS_CURRENT: u32 = 0;
+pub(crate) const XEMBED_FOCUS_FIRST: u32 = 1;
+pub(crate) const XEMBED_FOCUS_LAST: u32 = 2;
+
+
+
+impl XEmbedEmbedderController for ShellClient {
+
+    fn embed_client(&self, window: &mut Window, client: xcb::x::Window) -> Result<()> {
+        use xcb::Xid;
+
+        self.release_client(window)?;
+
+        self.connection.send_and_check_request(&xcb::x::ChangeWindowAttributes {
+            window: client,
+            value_list: &[xcb::x::Cw::EventMask(
+                xcb::x::EventMask::STRUCTURE_NOTIFY | xcb::x::EventMask::PROPERTY_CHANGE
+            )],
+        })
+        .or_else(|_| Err(Error::PlatformApiFailed("cannot select events of the embedded window")))?;
+
+        // Clients without _XEMBED_INFO are mapped immediately
+        let (_, client_flags) = self.get_xembed_info(client)?
+            .unwrap_or((XEMBED_PROTOCOL_VERSION, XEMBED_MAPPED));
+
+        // Keeps the client alive if this application terminates
+        self.connection.send_and_check_request(&xcb::x::ChangeSaveSet {
+            mode: xcb::x::SetMode::Insert,
+            window: client,
+        })
+        .or_else(|_| Err(Error::PlatformApiFailed("cannot add the embedded window to the save set")))?;
+
+        self.connection.send_and_check_request(&xcb::x::ReparentWindow {
+            window: client,
+            parent: window.handle,
+            x: 0,
+            y: 0,
+        })
+        .or_else(|_| Err(Error::PlatformApiFailed("cannot reparent the embedded window")))?;
+
+        self.xembed_clients.borrow_mut().insert(client, window.handle);
+
+        let is_focused = window.xembed_embedder.as_ref().is_some_and(|embedder| embedder.is_focused);
+
+        window.xembed_embedder = Some(X11XEmbedEmbedder {
+            client: Some(client),
+            client_flags,
+            is_focused,
+        });
+
+        self.resize_xembed_client(window)?;
+
+        // Version 0 is the only one, so it is the lowest version supported by both sides
+        let embedder_id = window.handle.resource_id();
+        self.send_xembed_message(client, XEMBED_EMBEDDED_NOTIFY, 0, embedder_id, XEMBED_PROTOCOL_VERSION)?;
+
+        if client_flags & XEMBED_MAPPED != 0 {
+            self.set_xembed_client_mapped(client, true)?;
+        }
+
+        if is_focused {
+            self.send_xembed_message(client, XEMBED_WINDOW_ACTIVATE, 0, 0, 0)?;
+            self.send_xembed_message(client, XEMBED_FOCUS_IN, XEMBED_FOCUS_CURRENT, 0, 0)?;
+        }
+
+        Ok(())
+    }
+
+
+    fn release_client(&self, window: &mut Window) -> Result<()> {
+        let is_embedded = window.xembed_embedder.as_ref().is_some_and(|embedder| embedder.client.is_some());
+
+        if is_embedded {
+            self.release_xembed_client(window)?;
+        }
+
+        Ok(())
+    }
+
+
+    fn get_embedded_client(&self, window: &Window) -> Option<xcb::x::Window> {
+        window.xembed_embedder.as_ref()?.client
+    }
+
+}
+
+
+impl XEmbedClientController for ShellClient {
+
+    fn enable_xembed(&self, window: &mut Window) -> Result<()> {
+        if window.xembed_client.is_some() {
+            return Ok(());
+        }
+
+        // Windows are created unmapped
+        self.set_xembed_info(window.handle, 0)?;
+
+        window.xembed_client = Some(X11XEmbedClient {
+            embedder: None,
+            flags: 0,
+        });
+
+        Ok(())
+    }
+
+
+    fn get_embedder(&self, window: &Window) -> Option<xcb::x::Window> {
+        window.xembed_client.as_ref()?.embedder
+    }
+
+}
+
+
+impl ShellClient {
+
+    /// Returns the protocol version and flags, or `None` if the window has no _XEMBED_INFO
+    pub(crate) fn get_xembed_info(&self, window_handle: X11WindowHandle) -> Result<Option<(u32, u32)>> {
+        let cookie = self.connection.send_request(&xcb::x::GetProperty {
+            delete: false,
+            window: window_handle,
+            property: self.atoms._XEMBED_INFO,
+            r#type: self.atoms._XEMBED_INFO,
+            long_offset: 0,
+            long_length: 2,
+        });
+
+        let reply = self.connection.wait_for_reply(cookie)
+            .or_else(|_| Err(Error::PlatformApiFailed("cannot get XEmbed info")))?;
+
+        match reply.value::<u32>() {
+            [version, flags, ..] => Ok(Some((*version, *flags))),
+            _ => Ok(None),
+        }
+    }
+
+
+    pub(crate) fn set_xembed_info(&self, window_handle: X11WindowHandle, flags: u32) -> Result<()> {
+        self.connection.send_and_check_request(&xcb::x::ChangeProperty {
+            mode: xcb::x::PropMode::Replace,
+            window: window_handle,
+            property: self.atoms._XEMBED_INFO,
+            r#type: self.atoms._XEMBED_INFO,
+            data: &[XEMBED_PROTOCOL_VERSION, flags],
+        })
+        .or_else(|_| Err(Error::PlatformApiFailed("cannot set XEmbed info")))
+    }
+
+
+    pub(crate) fn send_xembed_message(
+        &self, destination: X11WindowHandle, message: u32, detail: u32, data1: u32, data2: u32
+    )
+        -> Result<()>
+    {
+        let event = xcb::x::ClientMessageEvent::new(
+            destination,
+            self.atoms._XEMBED,
+            xcb::x::ClientMessageData::Data32([self.last_user_time.get(), message, detail, data1, data2])
+        );
+
+        self.connection.send_and_check_request(&xcb::x::SendEvent {
+            propagate: false,
+            destination: xcb::x::SendEventDest::Window(destination),
+            event_mask: xcb::x::EventMask::NO_EVENT,
+            event: &event
+        })
+        .or_else(|_| Err(Error::PlatformApiFailed("cannot send XEmbed message")))
+    }
+
+
+    pub(crate) fn set_xembed_client_mapped(&self, client: X11WindowHandle, is_mapped: bool) -> Result<()> {
+        if is_mapped {
+            self.connection.send_and_check_request(&xcb::x::MapWindow { window: client })
+                .or_else(|_| Err(Error::PlatformApiFailed("cannot map the embedded window")))
+        } else {
+            self.connection.send_and_check_request(&xcb::x::UnmapWindow { window: client })
+                .or_else(|_| Err(Error::PlatformApiFailed("cannot unmap the embedded window")))
+        }
+    }
+
+
+    /// The client always fills the embedder
+    pub(crate) fn resize_xembed_client(&self, window: &Window) -> Result<()> {
+        let Some(client) = window.xembed_embedder.as_ref().and_then(|embedder| embedder.client) else {
+            return Ok(());
+        };
+
+        self.connection.send_and_check_request(&xcb::x::ConfigureWindow {
+            window: client,
+            value_list: &[
+                xcb::x::ConfigWindow::Width(window.state.size.width),
+                xcb::x::ConfigWindow::Height(window.state.size.height),
+            ]
+        })
+        .or_else(|_| Err(Error::PlatformApiFailed("cannot resize the embedded window")))
+    }
+
+
+    pub(crate) fn release_xembed_client(&self, window: &mut Window) -> Result<()> {
+        let client = window.xembed_embedder.as_mut().and_then(|embedder| embedder.client.take()).unwrap();
+
+        self.xembed_clients.borrow_mut().remove(&client);
+
+        let requests = [
+            self.connection.send_request_checked(&xcb::x::ChangeWindowAttributes {
+                window: client,
+                value_list: &[xcb::x::Cw::EventMask(xcb::x::EventMask::NO_EVENT)],
+            }),
+
+            self.connection.send_request_checked(&xcb::x::UnmapWindow { window: client }),
+
+            self.connection.send_request_checked(&xcb::x::ReparentWindow {
+                window: client,
+                parent: self.get_default_window(),
+                x: 0,
+                y: 0,
+            }),
+
+            self.connection.send_request_checked(&xcb::x::ChangeSaveSet {
+                mode: xcb::x::SetMode::Delete,
+                window: client,
+            }),
+        ];
+
+        for request in requests {
+            self.connection.check_request(request)
+                .or_else(|_| Err(Error::PlatformApiFailed("cannot release the embedded window")))?;
+        }
+
+        Ok(())
+    }
+
+
+    /// Called when the client has been destroyed or reparented by someone else
+    pub(crate) fn forget_xembed_client(&self, window: &mut Window) {
+        if let Some(client) = window.xembed_embedder.as_mut().and_then(|embedder| embedder.client.take()) {
+            self.xembed_clients.borrow_mut().remove(&client);
+        }
+    }
+
+}
@@ -0,0 +1,326 @@
+use crate::types::*;
+
+
+/// X11 keycodes of the evdev and libinput drivers are Linux scancodes shifted by 8
+const X11_KEYCODE_OFFSET: u32 = 8;
+
+
+impl ShellClient {
+
+    /// Asks the server not to emulate key releases while a key is auto-repeated.
+    ///
+    /// Servers without detectable auto-repeat send a release before every repeated press. Such repeats cannot be
+    /// told apart from real presses, so they are reported as separate presses and releases.
+    pub(crate) fn init_keyboard(&self) -> Result<()> {
+        // The result is not needed, see above
+        let mut is_supported: i32 = 0;
+
+        unsafe {
+            xlib::XkbSetDetectableAutoRepeat(self.xdisplay, xlib::True, &mut is_supported);
+        }
+
+        Ok(())
+    }
+
+
+    /// Builds an Xlib key event that can be passed to Xlib text and keysym lookup functions
+    pub(crate) fn make_xkey_event(&self, event: &xcb::x::KeyPressEvent) -> xlib::XKeyEvent {
+        use xcb::Xid;
+
+        xlib::XKeyEvent {
+            type_: event.response_type() as i32 & 0x7F,
+            serial: event.sequence() as _,
+            send_event: (event.response_type() & 0x80 != 0) as i32,
+            display: self.xdisplay,
+            window: event.event().resource_id() as _,
+            root: event.root().resource_id() as _,
+            subwindow: event.child().resource_id() as _,
+            time: event.time() as _,
+            x: event.event_x() as i32,
+            y: event.event_y() as i32,
+            x_root: event.root_x() as i32,
+            y_root: event.root_y() as i32,
+            state: event.state().bits(),
+            keycode: event.detail() as u32,
+            same_screen: event.same_screen() as i32,
+        }
+    }
+
+
+    /// Looks up the keysym produced by the key with the current modifiers in the server keymap
+    pub(crate) fn lookup_keysym(&self, event: &xcb::x::KeyPressEvent) -> xlib::KeySym {
+        let mut xkey_event = self.make_xkey_event(event);
+        let mut keysym: xlib::KeySym = 0;
+
+        unsafe {
+            xlib::XLookupString(&mut xkey_event, null_mut(), 0, &mut keysym, null_mut());
+        }
+
+        keysym
+    }
+
+
+    /// Makes Xlib reload its copy of the server keymap
+    pub(crate) fn refresh_keyboard_mapping(&self, event: &xcb::x::MappingNotifyEvent) {
+        let mut xmapping_event = xlib::XMappingEvent {
+            type_: xlib::MappingNotify,
+            serial: event.sequence() as _,
+            send_event: xlib::False,
+            display: self.xdisplay,
+            event: 0,
+            request: event.request() as i32,
+            first_keycode: event.first_keycode() as i32,
+            count: event.count() as i32,
+        };
+
+        unsafe {
+            xlib::XRefreshKeyboardMapping(&mut xmapping_event);
+        }
+    }
+
+}
+
+
+pub(crate) fn translate_modifiers(state: xcb::x::KeyButMask) -> KeyModifiers {
+    use xcb::x::KeyButMask;
+
+    KeyModifiers {
+        shift: state.contains(KeyButMask::SHIFT),
+        control: state.contains(KeyButMask::CONTROL),
+        alt: state.contains(KeyButMask::MOD1),
+        meta: state.contains(KeyButMask::MOD4),
+        caps_lock: state.contains(KeyButMask::LOCK),
+        num_lock: state.contains(KeyButMask::MOD2),
+    }
+}
+
+
+pub(crate) fn translate_keycode(keycode: xcb::x::Keycode) -> KeyCode {
+    let scancode = (keycode as u32).wrapping_sub(X11_KEYCODE_OFFSET);
+
+    match scancode {
+        1 => KeyCode::Escape,
+        2 => KeyCode::Digit1,
+        3 => KeyCode::Digit2,
+        4 => KeyCode::Digit3,
+        5 => KeyCode::Digit4,
+        6 => KeyCode::Digit5,
+        7 => KeyCode::Digit6,
+        8 => KeyCode::Digit7,
+        9 => KeyCode::Digit8,
+        10 => KeyCode::Digit9,
+        11 => KeyCode::Digit0,
+        12 => KeyCode::Minus,
+        13 => KeyCode::Equal,
+        14 => KeyCode::Backspace,
+        15 => KeyCode::Tab,
+        16 => KeyCode::KeyQ,
+        17 => KeyCode::KeyW,
+        18 => KeyCode::KeyE,
+        19 => KeyCode::KeyR,
+        20 => KeyCode::KeyT,
+        21 => KeyCode::KeyY,
+        22 => KeyCode::KeyU,
+        23 => KeyCode::KeyI,
+        24 => KeyCode::KeyO,
+        25 => KeyCode::KeyP,
+        26 => KeyCode::BracketLeft,
+        27 => KeyCode::BracketRight,
+        28 => KeyCode::Enter,
+        29 => KeyCode::ControlLeft,
+        30 => KeyCode::KeyA,
+        31 => KeyCode::KeyS,
+        32 => KeyCode::KeyD,
+        33 => KeyCode::KeyF,
+        34 => KeyCode::KeyG,
+        35 => KeyCode::KeyH,
+        36 => KeyCode::KeyJ,
+        37 => KeyCode::KeyK,
+        38 => KeyCode::KeyL,
+        39 => KeyCode::Semicolon,
+        40 => KeyCode::Quote,
+        41 => KeyCode::Backquote,
+        42 => KeyCode::ShiftLeft,
+        43 => KeyCode::Backslash,
+        44 => KeyCode::KeyZ,
+        45 => KeyCode::KeyX,
+        46 => KeyCode::KeyC,
+        47 => KeyCode::KeyV,
+        48 => KeyCode::KeyB,
+        49 => KeyCode::KeyN,
+        50 => KeyCode::KeyM,
+        51 => KeyCode::Comma,
+        52 => KeyCode::Period,
+        53 => KeyCode::Slash,
+        54 => KeyCode::ShiftRight,
+        55 => KeyCode::NumpadMultiply,
+        56 => KeyCode::AltLeft,
+        57 => KeyCode::Space,
+        58 => KeyCode::CapsLock,
+        59 => KeyCode::F1,
+        60 => KeyCode::F2,
+        61 => KeyCode::F3,
+        62 => KeyCode::F4,
+        63 => KeyCode::F5,
+        64 => KeyCode::F6,
+        65 => KeyCode::F7,
+        66 => KeyCode::F8,
+        67 => KeyCode::F9,
+        68 => KeyCode::F10,
+        69 => KeyCode::NumLock,
+        70 => KeyCode::ScrollLock,
+        71 => KeyCode::Numpad7,
+        72 => KeyCode::Numpad8,
+        73 => KeyCode::Numpad9,
+        74 => KeyCode::NumpadSubtract,
+        75 => KeyCode::Numpad4,
+        76 => KeyCode::Numpad5,
+        77 => KeyCode::Numpad6,
+        78 => KeyCode::NumpadAdd,
+        79 => KeyCode::Numpad1,
+        80 => KeyCode::Numpad2,
+        81 => KeyCode::Numpad3,
+        82 => KeyCode::Numpad0,
+        83 => KeyCode::NumpadDecimal,
+        86 => KeyCode::IntlBackslash,
+        87 => KeyCode::F11,
+        88 => KeyCode::F12,
+        96 => KeyCode::NumpadEnter,
+        97 => KeyCode::ControlRight,
+        98 => KeyCode::NumpadDivide,
+        99 => KeyCode::PrintScreen,
+        100 => KeyCode::AltRight,
+        102 => KeyCode::Home,
+        103 => KeyCode::ArrowUp,
+        104 => KeyCode::PageUp,
+        105 => KeyCode::ArrowLeft,
+        106 => KeyCode::ArrowRight,
+        107 => KeyCode::End,
+        108 => KeyCode::ArrowDown,
+        109 => KeyCode::PageDown,
+        110 => KeyCode::Insert,
+        111 => KeyCode::Delete,
+        113 => KeyCode::AudioVolumeMute,
+        114 => KeyCode::AudioVolumeDown,
+        115 => KeyCode::AudioVolumeUp,
+        117 => KeyCode::NumpadEqual,
+        119 => KeyCode::Pause,
+        121 => KeyCode::NumpadComma,
+        125 => KeyCode::MetaLeft,
+        126 => KeyCode::MetaRight,
+        127 => KeyCode::ContextMenu,
+        183 => KeyCode::F13,
+        184 => KeyCode::F14,
+        185 => KeyCode::F15,
+        186 => KeyCode::F16,
+        187 => KeyCode::F17,
+        188 => KeyCode::F18,
+        189 => KeyCode::F19,
+        190 => KeyCode::F20,
+        191 => KeyCode::F21,
+        192 => KeyCode::F22,
+        193 => KeyCode::F23,
+        194 => KeyCode::F24,
+        _ => KeyCode::Unknown(keycode as u32),
+    }
+}
+
+
+pub(crate) fn translate_keysym(keysym: xlib::KeySym) -> LogicalKey {
+    if let Some(code) = keysym_to_named_key(keysym) {
+        return LogicalKey::Named(code);
+    }
+
+    keysym_to_char(keysym)
+        .map(LogicalKey::Character)
+        .unwrap_or(LogicalKey::Unidentified)
+}
+
+
+fn keysym_to_named_key(keysym: xlib::KeySym) -> Option<KeyCode> {
+    let function_key_codes = [
+        KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6,
+        KeyCode::F7, KeyCode::F8, KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12,
+        KeyCode::F13, KeyCode::F14, KeyCode::F15, KeyCode::F16, KeyCode::F17, KeyCode::F18,
+        KeyCode::F19, KeyCode::F20, KeyCode::F21, KeyCode::F22, KeyCode::F23, KeyCode::F24,
+    ];
+
+    let code = match keysym {
+        0xFF08 => KeyCode::Backspace,
+        0xFF09 | 0xFE20 => KeyCode::Tab,
+        0xFF0D | 0xFF8D => KeyCode::Enter,
+        0xFF13 => KeyCode::Pause,
+        0xFF14 => KeyCode::ScrollLock,
+        0xFF15 | 0xFF61 => KeyCode::PrintScreen,
+        0xFF1B => KeyCode::Escape,
+        0xFFFF | 0xFF9F => KeyCode::Delete,
+        0xFF50 | 0xFF95 => KeyCode::Home,
+        0xFF51 | 0xFF96 => KeyCode::ArrowLeft,
+        0xFF52 | 0xFF97 => KeyCode::ArrowUp,
+        0xFF53 | 0xFF98 => KeyCode::ArrowRight,
+        0xFF54 | 0xFF99 => KeyCode::ArrowDown,
+        0xFF55 | 0xFF9A => KeyCode::PageUp,
+        0xFF56 | 0xFF9B => KeyCode::PageDown,
+        0xFF57 | 0xFF9C => KeyCode::End,
+        0xFF63 | 0xFF9E => KeyCode::Insert,
+        0xFF67 => KeyCode::ContextMenu,
+        0xFF7F => KeyCode::NumLock,
+        0xFFBE..=0xFFD5 => function_key_codes[(keysym - 0xFFBE) as usize],
+        0xFFE1 => KeyCode::ShiftLeft,
+        0xFFE2 => KeyCode::ShiftRight,
+        0xFFE3 => KeyCode::ControlLeft,
+        0xFFE4 => KeyCode::ControlRight,
+        0xFFE5 => KeyCode::CapsLock,
+        0xFFE7 | 0xFFEB => KeyCode::MetaLeft,
+        0xFFE8 | 0xFFEC => KeyCode::MetaRight,
+        0xFFE9 => KeyCode::AltLeft,
+        0xFFEA | 0xFE03 => KeyCode::AltRight,
+        0x1008FF11 => KeyCode::AudioVolumeDown,
+        0x1008FF12 => KeyCode::AudioVolumeMute,
+        0x1008FF13 => KeyCode::AudioVolumeUp,
+        _ => return None,
+    };
+
+    Some(code)
+}
+
+
+/// Converts keysyms that represent characters.
+///
+/// Legacy keysyms are converted for Latin-1 and Cyrillic.
+/// Other scripts are expected to be mapped to Unicode keysyms by modern keymaps.
+fn keysym_to_char(keysym: xlib::KeySym) -> Option<char> {
+    /// Cyrillic keysyms `0x6C0..=0x6DF` follow the KOI8 order, `0x6E0..=0x6FF` are their uppercase versions
+    const KOI8_CYRILLIC: &str = "юабцдефгхийклмнопярстужвьызшэщчъ";
+
+    /// Cyrillic keysyms `0x6A1..=0x6AF`, `0x6B1..=0x6BF` are their uppercase versions
+    const EXTRA_CYRILLIC: &str = "ђѓёєѕіїјљњћќґўџ";
+
+    let codepoint = match keysym {
+        0x20..=0x7E | 0xA0..=0xFF => keysym as u32,
+
+        0xFF80 => ' ' as u32,
+        0xFFAA => '*' as u32,
+        0xFFAB => '+' as u32,
+        0xFFAC => ',' as u32,
+        0xFFAD => '-' as u32,
+        0xFFAE => '.' as u32,
+        0xFFAF => '/' as u32,
+        0xFFB0..=0xFFB9 => '0' as u32 + (keysym - 0xFFB0) as u32,
+        0xFFBD => '=' as u32,
+
+        0x6A1..=0x6AF => return EXTRA_CYRILLIC.chars().nth((keysym - 0x6A1) as usize),
+        0x6B1..=0x6BF => return EXTRA_CYRILLIC.chars().nth((keysym - 0x6B1) as usize)
+            .and_then(|character| character.to_uppercase().next()),
+        0x6B0 => '№' as u32,
+        0x6C0..=0x6DF => return KOI8_CYRILLIC.chars().nth((keysym - 0x6C0) as usize),
+        0x6E0..=0x6FF => return KOI8_CYRILLIC.chars().nth((keysym - 0x6E0) as usize)
+            .and_then(|character| character.to_uppercase().next()),
+
+        0x0100_0100..=0x0110_FFFF => (keysym - 0x0100_0000) as u32,
+
+        _ => return None,
+    };
+
+    char::from_u32(codepoint)
+}
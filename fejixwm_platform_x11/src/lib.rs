@@ -5,14 +5,29 @@
 extern crate fejixwm_core;
 pub(crate) use fejixwm_core as core;
 
-extern crate x11;
-extern crate xcb;
+pub extern crate x11;
+pub extern crate xcb;
+extern crate libc;
+
+#[cfg(feature = "graphics_egl")]
+extern crate khronos_egl;
+
+#[cfg(feature = "raw_window_handle")]
+extern crate raw_window_handle;
 
 mod types;
 mod core_impl;
 mod subsystems_impl;
 mod message_processing;
+mod keyboard_translation;
+mod mouse_translation;
+mod native_access;
+
+#[cfg(feature = "raw_window_handle")]
+mod raw_window_handle_impl;
+
 pub mod implementation;
 
 
-pub use types::*;
\ No newline at end of file
+pub use types::*;
+pub use native_access::*;
\ No newline at end of file
@@ -1,9 +1,24 @@
 use crate::{
     types::*,
-    core::events::*
+    core::events::*,
+    keyboard_translation::*,
+    mouse_translation::*,
+    implementation::xembed::*,
 };
 
 
+/// A pointer button event from either the core protocol or XInput
+struct X11PointerButton {
+    pub button: u32,
+    pub position: PixelPosition,
+    pub modifiers: KeyModifiers,
+    pub is_press: bool,
+
+    /// True if XInput has generated the event from another event (e.g. smooth scrolling)
+    pub is_emulated: bool,
+}
+
+
 /// Reduces function arguments boilerplate
 struct EventWrapper<'a, EventT> {
     pub window: Option<&'a mut Window>,
@@ -40,10 +55,24 @@ impl ShellClient {
     }
 
     fn handle_window_event(&self, wrapper: EventWrapper<xcb::Event>) -> Result<()> {
+        // Events of embedded foreign windows are delivered to their embedders
+        let is_xembed_client_event = self.get_event_window_handle(wrapper.event)
+            .is_some_and(|handle| handle != wrapper.window.as_ref().unwrap().handle);
+
+        if is_xembed_client_event {
+            return match wrapper.event {
+                xcb::Event::X(event) => self.handle_xembed_client_event(wrapper.with(event)),
+                _ => Ok(()),
+            };
+        }
+
         match wrapper.event {
             xcb::Event::X(event) =>
                 self.handle_x_event(wrapper.with(event)),
 
+            xcb::Event::Input(event) =>
+                self.handle_xinput_event(wrapper.with(event)),
+
             _ => Ok(())
         }
     }
@@ -54,6 +83,26 @@ impl ShellClient {
             xcb::Event::X(event) =>
                 self.handle_global_x_event(wrapper.with(event)),
 
+            xcb::Event::XFixes(xcb::xfixes::Event::SelectionNotify(event)) => {
+                if let Some(global) = &self.sys_redraw_subsystem {
+                    let mut redraw_windows = self.redraw_windows.borrow_mut();
+
+                    for window_handle in global.handle_selection_notify(&event) {
+                        if !redraw_windows.contains(&window_handle) {
+                            redraw_windows.push(window_handle);
+                        }
+                    }
+                }
+                Ok(())
+            }
+
+            xcb::Event::Input(xcb::xinput::Event::DeviceChanged(event)) => {
+                if let Some(xinput) = &self.xinput_subsystem {
+                    xinput.forget_device(event.source().id());
+                }
+                Ok(())
+            }
+
             _ => Ok(())
         }
     }
@@ -64,8 +113,44 @@ impl ShellClient {
             xcb::x::Event::ClientMessage(event) =>
                 self.handle_client_message(wrapper.with(event)),
 
-            xcb::x::Event::ResizeRequest(event) =>
-                self.handle_resize_request_event(wrapper.with(event)),
+            xcb::x::Event::ConfigureNotify(event) =>
+                self.handle_configure_notify(wrapper.with(event)),
+
+            xcb::x::Event::PropertyNotify(event) =>
+                self.handle_property_notify(wrapper.with(event)),
+
+            xcb::x::Event::Expose(event) =>
+                self.handle_expose(wrapper.with(event)),
+
+            xcb::x::Event::FocusIn(event) =>
+                self.handle_focus_change(wrapper.with(event), true),
+
+            xcb::x::Event::FocusOut(event) =>
+                self.handle_focus_change(wrapper.with(event), false),
+
+            xcb::x::Event::KeyPress(event) =>
+                self.handle_key_press(wrapper.with(event)),
+
+            xcb::x::Event::KeyRelease(event) =>
+                self.handle_key_release(wrapper.with(event)),
+
+            xcb::x::Event::ButtonPress(event) =>
+                self.handle_button_press(wrapper.with(event)),
+
+            xcb::x::Event::ButtonRelease(event) =>
+                self.handle_button_release(wrapper.with(event)),
+
+            xcb::x::Event::MotionNotify(event) =>
+                self.handle_pointer_motion(wrapper.with(event)),
+
+            xcb::x::Event::EnterNotify(event) =>
+                self.handle_pointer_enter(wrapper.with(event)),
+
+            xcb::x::Event::LeaveNotify(event) =>
+                self.handle_pointer_leave(wrapper.with(event)),
+
+            xcb::x::Event::ReparentNotify(event) =>
+                self.handle_reparent_notify(wrapper.with(event)),
 
             // TODO handle more events
             _ => Ok(())
@@ -78,6 +163,27 @@ impl ShellClient {
 
         let message_data = wrapper.event.data();
 
+        if wrapper.event.r#type() == self.atoms.FEJIXWM_TEXT_INPUT_EVENT {
+            return self.handle_text_input_event(wrapper);
+        }
+
+        if wrapper.event.r#type() == self.atoms.FEJIXWM_REDRAW_EVENT {
+            return self.handle_redraw_request(wrapper);
+        }
+
+        if wrapper.event.r#type() == self.atoms._XEMBED {
+            return self.handle_xembed_message(wrapper);
+        }
+
+        if wrapper.event.r#type() == self.atoms._NET_WM_FRAME_DRAWN {
+            return self.handle_frame_drawn(wrapper);
+        }
+
+        // Frame timings are only informational
+        if wrapper.event.r#type() == self.atoms._NET_WM_FRAME_TIMINGS {
+            return Ok(());
+        }
+
         if let xcb::x::ClientMessageData::Data32(data32) = wrapper.event.data() {
             let message_type = data32[0];
 
@@ -85,6 +191,8 @@ impl ShellClient {
                 self.handle_window_close(wrapper.with(&()))?;
             } else if message_type == self.atoms._NET_WM_PING.resource_id() {
                 self.handle_ping(wrapper)?;
+            } else if message_type == self.atoms._NET_WM_SYNC_REQUEST.resource_id() {
+                self.handle_sync_request(wrapper, data32)?;
             }
 
         }
@@ -93,6 +201,126 @@ impl ShellClient {
     }
 
 
+    fn handle_text_input_event(&self, mut wrapper: EventWrapper<xcb::x::ClientMessageEvent>) -> Result<()> {
+        let events = match wrapper.window.as_mut().unwrap().text_input.as_mut() {
+            Some(text_input) => text_input.take_preedit_events(),
+            None => return Ok(()),
+        };
+
+        for event in events {
+            (wrapper.handler)(Event::WindowEvent(event), wrapper.window.as_deref_mut());
+        }
+
+        Ok(())
+    }
+
+
+    fn handle_redraw_request(&self, mut wrapper: EventWrapper<xcb::x::ClientMessageEvent>) -> Result<()> {
+        let window = wrapper.window.as_mut().unwrap();
+        let compositor = self.get_compositor();
+
+        if let Some(sys_redraw) = window.sys_redraw.as_mut() {
+            if sys_redraw.should_defer_redraw(compositor) {
+                sys_redraw.is_redraw_deferred = true;
+
+                // Delivered if the compositor changes before it reports the frame
+                if let Some(global) = &self.sys_redraw_subsystem {
+                    global.deferred_windows.borrow_mut().push(window.handle);
+                }
+
+                return Ok(());
+            }
+        }
+
+        self.handle_full_redraw(wrapper)
+    }
+
+
+    fn handle_frame_drawn(&self, mut wrapper: EventWrapper<xcb::x::ClientMessageEvent>) -> Result<()> {
+        let xcb::x::ClientMessageData::Data32([value_lo, value_hi, ..]) = wrapper.event.data() else {
+            return Ok(());
+        };
+
+        let value = (value_lo as i64) | ((value_hi as i32 as i64) << 32);
+        let compositor = self.get_compositor();
+        let window = wrapper.window.as_mut().unwrap();
+
+        let is_redraw_deferred = match window.sys_redraw.as_mut() {
+            Some(sys_redraw) => sys_redraw.handle_frame_drawn(value, compositor),
+            None => false,
+        };
+
+        if is_redraw_deferred {
+            if let Some(global) = &self.sys_redraw_subsystem {
+                global.deferred_windows.borrow_mut().retain(|handle| *handle != window.handle);
+            }
+
+            self.handle_full_redraw(wrapper)?;
+        }
+
+        Ok(())
+    }
+
+
+    /// Returns the generation of the running compositor, `None` if it is unknown
+    fn get_compositor(&self) -> Option<u32> {
+        self.sys_redraw_subsystem.as_ref().and_then(|global| global.get_compositor())
+    }
+
+
+    /// The shell waits until the window is redrawn with the new size, so the sync request is followed by
+    /// ConfigureNotify and a redraw
+    fn handle_sync_request(&self, mut wrapper: EventWrapper<xcb::x::ClientMessageEvent>, data: [u32; 5])
+        -> Result<()>
+    {
+        if let Some(sys_redraw) = wrapper.window.as_mut().unwrap().sys_redraw.as_mut() {
+            sys_redraw.handle_sync_request(&data);
+        }
+
+        Ok(())
+    }
+
+
+    fn handle_full_redraw<EventT>(&self, mut wrapper: EventWrapper<EventT>) -> Result<()> {
+        let window = wrapper.window.as_mut().unwrap();
+        let size = &window.state.size;
+
+        // The whole window is repainted, so pending exposures are covered too
+        window.state.damage.clear();
+
+        let damage = vec![PixelRect::new(0, 0, size.width, size.height)];
+        self.handle_redraw(wrapper, damage)
+    }
+
+
+    fn handle_redraw<EventT>(&self, mut wrapper: EventWrapper<EventT>, damage: Vec<PixelRect>) -> Result<()> {
+        if let Some(sys_redraw) = wrapper.window.as_mut().unwrap().sys_redraw.as_mut() {
+            sys_redraw.lock_surface(self)?;
+        }
+
+        (wrapper.handler)(Event::WindowEvent(WindowEvent::Redraw { damage }), wrapper.window);
+        Ok(())
+    }
+
+
+    /// Expose events come in series, `count` is the number of events remaining in the series
+    fn handle_expose(&self, mut wrapper: EventWrapper<xcb::x::ExposeEvent>) -> Result<()> {
+        let event = wrapper.event;
+        let window = wrapper.window.as_mut().unwrap();
+
+        window.state.damage.push(PixelRect::new(
+            event.x() as i32, event.y() as i32, event.width() as u32, event.height() as u32
+        ));
+
+        if event.count() == 0 {
+            let damage = std::mem::take(&mut window.state.damage);
+            self.handle_redraw(wrapper, damage)?;
+        }
+
+        Ok(())
+    }
+
+
     fn handle_window_close(&self, wrapper: EventWrapper<()>) -> Result<()> {
         (wrapper.handler)(Event::WindowEvent(WindowEvent::Close), wrapper.window);
         Ok(())
@@ -112,17 +340,504 @@ impl ShellClient {
     }
 
 
-    fn handle_resize_request_event(&self, mut wrapper: EventWrapper<xcb::x::ResizeRequestEvent>) -> Result<()> {
+    fn handle_configure_notify(&self, mut wrapper: EventWrapper<xcb::x::ConfigureNotifyEvent>) -> Result<()> {
         let new_size = PixelSize::new(wrapper.event.width() as u32, wrapper.event.height() as u32);
+
+        let event_position = PixelPosition::new(wrapper.event.x() as i32, wrapper.event.y() as i32);
+
+        // Real events contain the position relative to the parent window, which is often the window manager's
+        // frame. Synthetic events sent by the window manager contain the position relative to the root window.
+        let is_synthetic = wrapper.event.response_type() & 0x80 != 0;
+
         let window = wrapper.window.as_mut().unwrap();
 
-        if window.state.size != new_size {
-            window.state.size = new_size.clone();
-    
+        // The window manager sends synthetic events when it moves the frame, so real events only report movement
+        // within the parent. This avoids querying the root position on every event of an interactive resize.
+        let new_position = if is_synthetic {
+            event_position
+        } else if window.is_child {
+            window.state.parent_position = event_position.clone();
+            event_position
+        } else {
+            let last_parent_position = std::mem::replace(&mut window.state.parent_position, event_position);
+
+            PixelPosition::new(
+                window.state.position.x + event_position.x - last_parent_position.x,
+                window.state.position.y + event_position.y - last_parent_position.y,
+            )
+        };
+
+        let is_resized = window.state.size != new_size;
+        let is_moved = window.state.position != new_position;
+
+        window.state.size = new_size.clone();
+        window.state.position = new_position;
+
+        // The first event after a request is its result, possibly adjusted or refused by the window manager.
+        // ICCCM window managers send a synthetic event even if the size has not changed.
+        window.pending_size = None;
+
+        if is_resized {
+            self.resize_xembed_client(window)?;
+
             let event = Event::WindowEvent(WindowEvent::Resize { new_size });
-            (wrapper.handler)(event, wrapper.window);
+            (wrapper.handler)(event, wrapper.window.as_deref_mut());
         }
-        
+
+        if is_moved {
+            let event = Event::WindowEvent(WindowEvent::Move { new_position });
+            (wrapper.handler)(event, wrapper.window.as_deref_mut());
+        }
+
+        let has_sync_request = wrapper.window.as_ref().unwrap().sys_redraw.as_ref()
+            .is_some_and(|sys_redraw| sys_redraw.has_sync_request());
+
+        // The shell waits for a frame even if the geometry has not changed
+        if has_sync_request {
+            self.handle_full_redraw(wrapper)?;
+        }
+
+        Ok(())
+    }
+
+
+    fn handle_property_notify(&self, mut wrapper: EventWrapper<xcb::x::PropertyNotifyEvent>) -> Result<()> {
+        if wrapper.event.atom() == self.atoms._NET_FRAME_EXTENTS {
+            let frame_extents = self.get_window_frame_extents(wrapper.event.window())?;
+            wrapper.window.as_mut().unwrap().state.frame_extents = frame_extents;
+        }
+
+        Ok(())
+    }
+
+
+    /// Handles messages sent by the embedder to the client window and by the client to the embedder window
+    fn handle_xembed_message(&self, mut wrapper: EventWrapper<xcb::x::ClientMessageEvent>) -> Result<()> {
+        use xcb::XidNew;
+
+        let xcb::x::ClientMessageData::Data32([_time, message, detail, data1, _data2]) = wrapper.event.data() else {
+            return Ok(());
+        };
+
+        let window = wrapper.window.as_mut().unwrap();
+
+        if let Some(embedder) = &window.xembed_embedder {
+            let Some(client) = embedder.client else {
+                return Ok(());
+            };
+
+            match message {
+                // There are no other widgets to pass the focus to, so it wraps around to the client
+                XEMBED_FOCUS_NEXT => self.send_xembed_message(client, XEMBED_FOCUS_IN, XEMBED_FOCUS_FIRST, 0, 0)?,
+                XEMBED_FOCUS_PREV => self.send_xembed_message(client, XEMBED_FOCUS_IN, XEMBED_FOCUS_LAST, 0, 0)?,
+
+                XEMBED_REQUEST_FOCUS if embedder.is_focused => {
+                    self.send_xembed_message(client, XEMBED_FOCUS_IN, XEMBED_FOCUS_CURRENT, 0, 0)?;
+                }
+
+                // The client is notified when the embedder receives the focus
+                XEMBED_REQUEST_FOCUS => {
+                    self.connection.send_and_check_request(&xcb::x::SetInputFocus {
+                        revert_to: xcb::x::InputFocus::Parent,
+                        focus: window.handle,
+                        time: self.last_user_time.get(),
+                    })
+                    .or_else(|_| Err(Error::PlatformApiFailed("cannot set input focus")))?;
+                }
+
+                _ => {}
+            }
+
+            return Ok(());
+        }
+
+        let Some(xembed_client) = &mut window.xembed_client else {
+            return Ok(());
+        };
+
+        let event = match message {
+            XEMBED_EMBEDDED_NOTIFY => {
+                xembed_client.embedder = Some(unsafe { X11WindowHandle::new(data1) });
+                return Ok(());
+            }
+
+            XEMBED_FOCUS_IN => WindowEvent::FocusGained,
+            XEMBED_FOCUS_OUT => WindowEvent::FocusLost,
+            XEMBED_WINDOW_ACTIVATE => WindowEvent::ActivationChange { is_active: true },
+            XEMBED_WINDOW_DEACTIVATE => WindowEvent::ActivationChange { is_active: false },
+            XEMBED_MODALITY_ON => WindowEvent::ModalityChange { is_modal: true },
+            XEMBED_MODALITY_OFF => WindowEvent::ModalityChange { is_modal: false },
+            _ => return Ok(()),
+        };
+
+        if let WindowEvent::FocusGained | WindowEvent::FocusLost = event {
+            let is_focused = matches!(event, WindowEvent::FocusGained);
+
+            if let Some(text_input) = &window.text_input {
+                text_input.set_focus(is_focused);
+            }
+
+            if !is_focused {
+                window.state.pressed_keys = [false; 256];
+            }
+        }
+
+        (wrapper.handler)(Event::WindowEvent(event), wrapper.window);
+
+        Ok(())
+    }
+
+
+    /// Handles events of the foreign window embedded into the window
+    fn handle_xembed_client_event(&self, mut wrapper: EventWrapper<xcb::x::Event>) -> Result<()> {
+        let window = wrapper.window.as_mut().unwrap();
+
+        let Some(client) = window.xembed_embedder.as_ref().and_then(|embedder| embedder.client) else {
+            return Ok(());
+        };
+
+        match wrapper.event {
+            xcb::x::Event::PropertyNotify(event) if event.atom() == self.atoms._XEMBED_INFO => {
+                let flags = self.get_xembed_info(client)?.map(|(_, flags)| flags).unwrap_or(0);
+                let embedder = window.xembed_embedder.as_mut().unwrap();
+
+                if (embedder.client_flags ^ flags) & XEMBED_MAPPED != 0 {
+                    self.set_xembed_client_mapped(client, flags & XEMBED_MAPPED != 0)?;
+                }
+
+                embedder.client_flags = flags;
+            }
+
+            xcb::x::Event::DestroyNotify(_) => {
+                self.forget_xembed_client(window);
+            }
+
+            // The client has left on its own
+            xcb::x::Event::ReparentNotify(event) if event.parent() != window.handle => {
+                self.forget_xembed_client(window);
+
+                // The client may have been destroyed in the meantime
+                self.connection.send_and_check_request(&xcb::x::ChangeWindowAttributes {
+                    window: client,
+                    value_list: &[xcb::x::Cw::EventMask(xcb::x::EventMask::NO_EVENT)],
+                })
+                .ok();
+            }
+
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+
+    /// Window managers reparent windows into their frames.
+    /// An embedder releases its client by reparenting it to the root window.
+    fn handle_reparent_notify(&self, mut wrapper: EventWrapper<xcb::x::ReparentNotifyEvent>) -> Result<()> {
+        let parent_position = PixelPosition::new(wrapper.event.x() as i32, wrapper.event.y() as i32);
+
+        let new_position = match wrapper.window.as_ref().unwrap().is_child {
+            true => parent_position.clone(),
+            false => self.get_window_root_position(wrapper.event.window())?,
+        };
+
+        let window = wrapper.window.as_mut().unwrap();
+        let is_moved = window.state.position != new_position;
+
+        window.state.position = new_position;
+        window.state.parent_position = parent_position;
+
+        if let Some(xembed_client) = &mut window.xembed_client {
+            if wrapper.event.parent() == self.get_default_window() {
+                xembed_client.embedder = None;
+            }
+        }
+
+        if is_moved {
+            let event = Event::WindowEvent(WindowEvent::Move { new_position });
+            (wrapper.handler)(event, wrapper.window.as_deref_mut());
+        }
+
+        Ok(())
+    }
+
+
+    fn handle_focus_change(&self, mut wrapper: EventWrapper<xcb::x::FocusInEvent>, is_focused: bool) -> Result<()> {
+        use xcb::x::{NotifyMode, NotifyDetail};
+
+        // Keyboard grabs (e.g. by the shell's shortcuts) and focus changes of the pointer's window
+        // do not change the focused window
+        if matches!(wrapper.event.mode(), NotifyMode::Grab | NotifyMode::Ungrab)
+            || wrapper.event.detail() == NotifyDetail::Pointer
+        {
+            return Ok(());
+        }
+
+        let window = wrapper.window.as_mut().unwrap();
+
+        if let Some(text_input) = &window.text_input {
+            text_input.set_focus(is_focused);
+        }
+
+        // Key releases are not reported to unfocused windows
+        if !is_focused {
+            window.state.pressed_keys = [false; 256];
+        }
+
+        if let Some(embedder) = &mut window.xembed_embedder {
+            embedder.is_focused = is_focused;
+
+            if let Some(client) = embedder.client {
+                if is_focused {
+                    self.send_xembed_message(client, XEMBED_WINDOW_ACTIVATE, 0, 0, 0)?;
+                    self.send_xembed_message(client, XEMBED_FOCUS_IN, XEMBED_FOCUS_CURRENT, 0, 0)?;
+                } else {
+                    self.send_xembed_message(client, XEMBED_FOCUS_OUT, 0, 0, 0)?;
+                    self.send_xembed_message(client, XEMBED_WINDOW_DEACTIVATE, 0, 0, 0)?;
+                }
+            }
+        }
+
+        let event = if is_focused { WindowEvent::FocusGained } else { WindowEvent::FocusLost };
+        (wrapper.handler)(Event::WindowEvent(event), wrapper.window);
+
+        Ok(())
+    }
+
+
+    fn handle_key_press(&self, mut wrapper: EventWrapper<xcb::x::KeyPressEvent>) -> Result<()> {
+        if let Some(client) = Self::get_mapped_xembed_client(wrapper.window.as_ref().unwrap()) {
+            let event = xcb::x::KeyPressEvent::new(
+                wrapper.event.detail(), wrapper.event.time(), wrapper.event.root(), client, xcb::x::WINDOW_NONE,
+                wrapper.event.root_x(), wrapper.event.root_y(), wrapper.event.event_x(), wrapper.event.event_y(),
+                wrapper.event.state(), wrapper.event.same_screen()
+            );
+
+            return self.forward_key_event(client, &event);
+        }
+
+        let keycode = wrapper.event.detail();
+        let window = wrapper.window.as_mut().unwrap();
+
+        self.last_user_time.set(wrapper.event.time());
+
+        let is_repeat = window.state.pressed_keys[keycode as usize];
+        window.state.pressed_keys[keycode as usize] = true;
+
+        let text = window.text_input.as_mut()
+            .and_then(|text_input| text_input.lookup_text(self, wrapper.event));
+
+        // Input methods commit text with key events that do not correspond to physical keys
+        if keycode != 0 {
+            let event = Event::WindowEvent(WindowEvent::KeyPress {
+                code: translate_keycode(keycode),
+                key: translate_keysym(self.lookup_keysym(wrapper.event)),
+                modifiers: translate_modifiers(wrapper.event.state()),
+                is_repeat,
+            });
+
+            (wrapper.handler)(event, wrapper.window.as_deref_mut());
+        }
+
+        if let Some(text) = text {
+            (wrapper.handler)(Event::WindowEvent(WindowEvent::TextInput { text }), wrapper.window);
+        }
+
+        Ok(())
+    }
+
+
+    /// The embedder keeps the keyboard focus and forwards key events to the focused client
+    fn get_mapped_xembed_client(window: &Window) -> Option<X11WindowHandle> {
+        let embedder = window.xembed_embedder.as_ref()?;
+
+        if embedder.client_flags & XEMBED_MAPPED != 0 {
+            embedder.client
+        } else {
+            None
+        }
+    }
+
+
+    fn forward_key_event(&self, client: X11WindowHandle, event: &impl xcb::BaseEvent) -> Result<()> {
+        self.connection.send_and_check_request(&xcb::x::SendEvent {
+            propagate: false,
+            destination: xcb::x::SendEventDest::Window(client),
+            event_mask: xcb::x::EventMask::NO_EVENT,
+            event,
+        })
+        .or_else(|_| Err(Error::PlatformApiFailed("cannot forward key event")))
+    }
+
+
+    fn handle_key_release(&self, mut wrapper: EventWrapper<xcb::x::KeyReleaseEvent>) -> Result<()> {
+        if let Some(client) = Self::get_mapped_xembed_client(wrapper.window.as_ref().unwrap()) {
+            let event = xcb::x::KeyReleaseEvent::new(
+                wrapper.event.detail(), wrapper.event.time(), wrapper.event.root(), client, xcb::x::WINDOW_NONE,
+                wrapper.event.root_x(), wrapper.event.root_y(), wrapper.event.event_x(), wrapper.event.event_y(),
+                wrapper.event.state(), wrapper.event.same_screen()
+            );
+
+            return self.forward_key_event(client, &event);
+        }
+
+        let keycode = wrapper.event.detail();
+        let window = wrapper.window.as_mut().unwrap();
+
+        window.state.pressed_keys[keycode as usize] = false;
+
+        let event = Event::WindowEvent(WindowEvent::KeyRelease {
+            code: translate_keycode(keycode),
+            key: translate_keysym(self.lookup_keysym(wrapper.event)),
+            modifiers: translate_modifiers(wrapper.event.state()),
+        });
+
+        (wrapper.handler)(event, wrapper.window);
+
+        Ok(())
+    }
+
+
+    fn handle_button_press(&self, wrapper: EventWrapper<xcb::x::ButtonPressEvent>) -> Result<()> {
+        let event = wrapper.event;
+        self.last_user_time.set(event.time());
+
+        self.handle_pointer_button(wrapper, X11PointerButton {
+            button: event.detail() as u32,
+            position: PixelPosition::new(event.event_x() as i32, event.event_y() as i32),
+            modifiers: translate_modifiers(event.state()),
+            is_press: true,
+            is_emulated: false,
+        })
+    }
+
+
+    fn handle_button_release(&self, wrapper: EventWrapper<xcb::x::ButtonReleaseEvent>) -> Result<()> {
+        let event = wrapper.event;
+
+        self.handle_pointer_button(wrapper, X11PointerButton {
+            button: event.detail() as u32,
+            position: PixelPosition::new(event.event_x() as i32, event.event_y() as i32),
+            modifiers: translate_modifiers(event.state()),
+            is_press: false,
+            is_emulated: false,
+        })
+    }
+
+
+    fn handle_pointer_button<EventT>(&self, wrapper: EventWrapper<EventT>, button: X11PointerButton) -> Result<()> {
+        if is_scroll_button(button.button) {
+            // Emulated scroll buttons duplicate the smooth scrolling reported with XInput motion events
+            if button.is_press && !button.is_emulated {
+                let delta = translate_scroll_button(button.button);
+                (wrapper.handler)(Event::WindowEvent(WindowEvent::Scroll { delta }), wrapper.window);
+            }
+
+            return Ok(());
+        }
+
+        let event = if button.is_press {
+            WindowEvent::MouseButtonPress {
+                button: translate_button(button.button),
+                position: button.position,
+                modifiers: button.modifiers,
+            }
+        } else {
+            WindowEvent::MouseButtonRelease {
+                button: translate_button(button.button),
+                position: button.position,
+                modifiers: button.modifiers,
+            }
+        };
+
+        (wrapper.handler)(Event::WindowEvent(event), wrapper.window);
+
+        Ok(())
+    }
+
+
+    fn handle_pointer_motion(&self, wrapper: EventWrapper<xcb::x::MotionNotifyEvent>) -> Result<()> {
+        let event = Event::WindowEvent(WindowEvent::PointerMove {
+            position: PixelPosition::new(wrapper.event.event_x() as i32, wrapper.event.event_y() as i32),
+        });
+
+        (wrapper.handler)(event, wrapper.window);
+
+        Ok(())
+    }
+
+
+    fn handle_pointer_enter(&self, wrapper: EventWrapper<xcb::x::EnterNotifyEvent>) -> Result<()> {
+        if let Some(xinput) = &self.xinput_subsystem {
+            xinput.reset_scroll_values();
+        }
+
+        let event = Event::WindowEvent(WindowEvent::PointerEnter {
+            position: PixelPosition::new(wrapper.event.event_x() as i32, wrapper.event.event_y() as i32),
+        });
+
+        (wrapper.handler)(event, wrapper.window);
+
+        Ok(())
+    }
+
+
+    fn handle_pointer_leave(&self, wrapper: EventWrapper<xcb::x::LeaveNotifyEvent>) -> Result<()> {
+        (wrapper.handler)(Event::WindowEvent(WindowEvent::PointerLeave), wrapper.window);
+        Ok(())
+    }
+
+
+    fn handle_xinput_event(&self, wrapper: EventWrapper<xcb::xinput::Event>) -> Result<()> {
+        match wrapper.event {
+            xcb::xinput::Event::ButtonPress(event) =>
+                self.handle_xinput_button(wrapper.with(event), true),
+
+            xcb::xinput::Event::ButtonRelease(event) =>
+                self.handle_xinput_button(wrapper.with(event), false),
+
+            xcb::xinput::Event::Motion(event) =>
+                self.handle_xinput_motion(wrapper.with(event)),
+
+            _ => Ok(())
+        }
+    }
+
+
+    fn handle_xinput_button(&self, wrapper: EventWrapper<xcb::xinput::ButtonPressEvent>, is_press: bool)
+        -> Result<()>
+    {
+        let event = wrapper.event;
+
+        if is_press {
+            self.last_user_time.set(event.time());
+        }
+
+        self.handle_pointer_button(wrapper, X11PointerButton {
+            button: event.detail(),
+            position: fp1616_to_position(event.event_x(), event.event_y()),
+            modifiers: translate_modifiers(xcb::x::KeyButMask::from_bits_truncate(event.mods().effective)),
+            is_press,
+            is_emulated: event.flags().contains(xcb::xinput::PointerEventFlags::POINTER_EMULATED),
+        })
+    }
+
+
+    fn handle_xinput_motion(&self, mut wrapper: EventWrapper<xcb::xinput::MotionEvent>) -> Result<()> {
+        let xinput = self.xinput_subsystem.as_ref().unwrap();
+
+        if X11GlobalXInputSubsystem::has_pointer_moved(wrapper.event) {
+            let event = Event::WindowEvent(WindowEvent::PointerMove {
+                position: fp1616_to_position(wrapper.event.event_x(), wrapper.event.event_y()),
+            });
+
+            (wrapper.handler)(event, wrapper.window.as_deref_mut());
+        }
+
+        if let Some(delta) = xinput.get_scroll_delta(self, wrapper.event) {
+            (wrapper.handler)(Event::WindowEvent(WindowEvent::Scroll { delta }), wrapper.window);
+        }
+
         Ok(())
     }
 
@@ -132,6 +847,11 @@ impl ShellClient {
             xcb::x::Event::ClientMessage(event) =>
                 self.handle_global_client_message(wrapper.with(event)),
 
+            xcb::x::Event::MappingNotify(event) => {
+                self.refresh_keyboard_mapping(event);
+                Ok(())
+            }
+
             _ => Ok(())
         }
     }
@@ -203,7 +923,20 @@ impl ShellClient {
                 }
             }
 
-            _ => todo!("Handle extraction of window IDs from other X11 events")
+            xcb::Event::Input(event) => {
+                use xcb::xinput::Event::*;
+                match event {
+                    ButtonPress(event) => Some(event.event()),
+                    ButtonRelease(event) => Some(event.event()),
+                    Motion(event) => Some(event.event()),
+
+                    // Device events that are not bound to windows
+                    _ => None,
+                }
+            }
+
+            // Events of other extensions (XKB, Sync, SHM etc.) are not bound to windows
+            _ => None,
         }
     }
 
@@ -0,0 +1,44 @@
+use crate::types::*;
+
+
+/// Core X11 buttons 4-7 are emulated by the server for scrolling
+pub(crate) fn is_scroll_button(button: u32) -> bool {
+    (4..=7).contains(&button)
+}
+
+
+pub(crate) fn translate_scroll_button(button: u32) -> ScrollDelta {
+    let (steps_x, steps_y) = match button {
+        4 => (0, -1),
+        5 => (0, 1),
+        6 => (-1, 0),
+        7 => (1, 0),
+        _ => (0, 0),
+    };
+
+    ScrollDelta {
+        steps_x,
+        steps_y,
+        x: steps_x as f64,
+        y: steps_y as f64,
+        unit: ScrollUnit::Lines,
+    }
+}
+
+
+/// Converts XInput window coordinates from 16.16 fixed point numbers
+pub(crate) fn fp1616_to_position(x: xcb::xinput::Fp1616, y: xcb::xinput::Fp1616) -> PixelPosition {
+    PixelPosition::new(x >> 16, y >> 16)
+}
+
+
+pub(crate) fn translate_button(button: u32) -> MouseButton {
+    match button {
+        1 => MouseButton::Left,
+        2 => MouseButton::Middle,
+        3 => MouseButton::Right,
+        8 => MouseButton::Back,
+        9 => MouseButton::Forward,
+        _ => MouseButton::Other(button),
+    }
+}
@@ -0,0 +1,94 @@
+//! Access to the native X11 objects, for calling X11 functionality that the library does not wrap.
+//!
+//! The objects are owned by the library. Changing their state (e.g. destroying a window or reading events from
+//! the connection) leads to undefined behaviour of the library.
+
+use crate::*;
+
+
+pub trait X11ShellClientExt {
+
+    fn xcb_connection(&self) -> &xcb::Connection;
+
+    /// Xlib display that shares the connection with [X11ShellClientExt::xcb_connection]
+    fn xlib_display(&self) -> *mut xlib::Display;
+
+    fn default_screen(&self) -> &xcb::x::Screen;
+
+    fn default_screen_number(&self) -> i32;
+
+    /// Wraps a window created by another party, e.g. a plugin host. The window is not destroyed by the library.
+    ///
+    /// The window has no canvas, it must be released with [X11ShellClientExt::release_window].
+    fn adopt_window(&self, xid: xcb::x::Window) -> Result<Window>;
+
+    /// Stops receiving the events of an adopted window
+    fn release_window(&self, window: Window) -> Result<()>;
+
+}
+
+
+pub trait X11WindowExt {
+
+    fn xid(&self) -> xcb::x::Window;
+
+}
+
+
+pub trait X11ShellMessageExt {
+
+    /// Internal messages of the library are delivered as client messages
+    fn raw_event(&self) -> &xcb::Event;
+
+}
+
+
+impl X11ShellClientExt for ShellClient {
+
+    fn xcb_connection(&self) -> &xcb::Connection {
+        &self.connection
+    }
+
+    fn xlib_display(&self) -> *mut xlib::Display {
+        self.xdisplay
+    }
+
+    fn default_screen(&self) -> &xcb::x::Screen {
+        self.get_default_screen()
+    }
+
+    fn default_screen_number(&self) -> i32 {
+        self.default_screen_number
+    }
+
+    fn adopt_window(&self, xid: xcb::x::Window) -> Result<Window> {
+        self.adopt_window(xid)
+    }
+
+    fn release_window(&self, window: Window) -> Result<()> {
+        if !window.is_foreign {
+            return Err(Error::InvalidArgument);
+        }
+
+        self.drop_window(window)
+    }
+
+}
+
+
+impl X11WindowExt for Window {
+
+    fn xid(&self) -> xcb::x::Window {
+        self.handle
+    }
+
+}
+
+
+impl X11ShellMessageExt for ShellMessage {
+
+    fn raw_event(&self) -> &xcb::Event {
+        &self.event
+    }
+
+}
@@ -0,0 +1,73 @@
+//! The trait implementations return the XCB handles. Libraries that require Xlib can use the Xlib handles instead,
+//! both refer to the same connection.
+//!
+//! Renderers that present through the handles must call [ShellClientTrait::notify_frame_presented] after every
+//! presented frame.
+
+use crate::*;
+
+use raw_window_handle::{
+    HasDisplayHandle, HasWindowHandle, DisplayHandle, WindowHandle, HandleError,
+    RawDisplayHandle, RawWindowHandle, XcbDisplayHandle, XcbWindowHandle, XlibDisplayHandle, XlibWindowHandle,
+};
+
+use std::{
+    num::NonZeroU32,
+    os::raw::c_ulong,
+    ptr::NonNull,
+};
+
+
+impl ShellClient {
+
+    pub fn raw_xcb_display_handle(&self) -> RawDisplayHandle {
+        let connection = NonNull::new(self.connection.get_raw_conn() as *mut ffi::c_void);
+        XcbDisplayHandle::new(connection, self.default_screen_number).into()
+    }
+
+
+    pub fn raw_xlib_display_handle(&self) -> RawDisplayHandle {
+        let display = NonNull::new(self.xdisplay as *mut ffi::c_void);
+        XlibDisplayHandle::new(display, self.default_screen_number).into()
+    }
+
+}
+
+
+impl Window {
+
+    pub fn raw_xcb_window_handle(&self) -> RawWindowHandle {
+        use xcb::Xid;
+
+        // Window identifiers are never 0
+        let mut handle = XcbWindowHandle::new(NonZeroU32::new(self.handle.resource_id()).unwrap());
+        handle.visual_id = NonZeroU32::new(self.visualid);
+        handle.into()
+    }
+
+
+    pub fn raw_xlib_window_handle(&self) -> RawWindowHandle {
+        use xcb::Xid;
+
+        let mut handle = XlibWindowHandle::new(self.handle.resource_id() as c_ulong);
+        handle.visual_id = self.visualid as c_ulong;
+        handle.into()
+    }
+
+}
+
+
+impl HasDisplayHandle for ShellClient {
+    fn display_handle(&self) -> std::result::Result<DisplayHandle<'_>, HandleError> {
+        // The connection lives as long as the client
+        Ok(unsafe { DisplayHandle::borrow_raw(self.raw_xcb_display_handle()) })
+    }
+}
+
+
+impl HasWindowHandle for Window {
+    fn window_handle(&self) -> std::result::Result<WindowHandle<'_>, HandleError> {
+        // The window is destroyed only when the canvas consumes it
+        Ok(unsafe { WindowHandle::borrow_raw(self.raw_xcb_window_handle()) })
+    }
+}
@@ -1,9 +1,15 @@
 use crate::types::*;
 
+use std::os::raw::c_ulong;
+
 
 impl X11GlobalTextInputSubsystem {
     pub fn new(client: &ShellClient) -> Result<Self> {
         let input_method = unsafe {
+            // Input methods and compose sequences depend on the user's locale
+            libc::setlocale(libc::LC_CTYPE, c"".as_ptr());
+            xlib::XSetLocaleModifiers(c"".as_ptr());
+
             xlib::XOpenIM(client.xdisplay, null_mut(), null_mut(), null_mut())
         };
         
@@ -12,11 +18,54 @@ impl X11GlobalTextInputSubsystem {
         }
 
         Ok(Self {
-            input_method
+            input_method,
+            preedit_windows: Rc::new(RefCell::new(Vec::new())),
         })
     }
 
 
+    /// Returns the preferred input style among the styles supported by the input method
+    fn choose_input_style(&self) -> Result<c_ulong> {
+        let preferred_styles = [
+            xlib::XIMPreeditCallbacks | xlib::XIMStatusNothing,
+            xlib::XIMPreeditCallbacks | xlib::XIMStatusNone,
+            xlib::XIMPreeditPosition | xlib::XIMStatusNothing,
+            xlib::XIMPreeditPosition | xlib::XIMStatusNone,
+            xlib::XIMPreeditNothing | xlib::XIMStatusNothing,
+            xlib::XIMPreeditNothing | xlib::XIMStatusNone,
+            xlib::XIMPreeditNone | xlib::XIMStatusNone,
+        ];
+
+        let query_input_style = ffi::CString::new(xlib::XNQueryInputStyle).unwrap();
+        let mut styles: *mut X11InputStyles = null_mut();
+
+        let failed_argument = unsafe {
+            xlib::XGetIMValues(
+                self.input_method,
+                query_input_style.as_ptr(), &mut styles as *mut *mut X11InputStyles,
+                null::<u8>()
+            )
+        };
+
+        if !failed_argument.is_null() || styles.is_null() {
+            return Err(Error::PlatformApiFailed("cannot query input styles"));
+        }
+
+        let supported_styles = unsafe {
+            std::slice::from_raw_parts((*styles).supported_styles, (*styles).count_styles as usize).to_vec()
+        };
+
+        unsafe {
+            xlib::XFree(styles as *mut ffi::c_void);
+        }
+
+        preferred_styles.iter()
+            .map(|style| *style as c_ulong)
+            .find(|style| supported_styles.contains(style))
+            .ok_or(Error::PlatformApiFailed("no supported input style"))
+    }
+
+
     pub fn destroy(&self) {
         unsafe {
             xlib::XCloseIM(self.input_method);
@@ -25,6 +74,14 @@ impl X11GlobalTextInputSubsystem {
 }
 
 
+/// Mirrors Xlib's `XIMStyles`
+#[repr(C)]
+struct X11InputStyles {
+    count_styles: std::os::raw::c_ushort,
+    supported_styles: *mut c_ulong,
+}
+
+
 
 impl X11SysRedrawSubsystem {
     pub fn new(client: &ShellClient, window_handle: X11WindowHandle) -> Result<Self> {
@@ -35,29 +92,56 @@ impl X11SysRedrawSubsystem {
 
 
     pub fn destroy(&self, client: &ShellClient) -> Result<()> {
-        client.connection.send_and_check_request(&xcb::sync::DestroyCounter {
-            counter: self.sync_counter,
-        })
-        .or_else(|_| Err(Error::PlatformApiFailed("cannot destroy sync counter")))?;
+        for counter in [self.sync_counter, self.extended_sync_counter] {
+            client.connection.send_and_check_request(&xcb::sync::DestroyCounter { counter })
+                .or_else(|_| Err(Error::PlatformApiFailed("cannot destroy sync counter")))?;
+        }
 
         Ok(())
     }
 
 
     fn create(client: &ShellClient, window_handle: X11WindowHandle) -> Result<Self> {
-        let sync_counter = client.connection.generate_id();
-        let sync_value = xcb::sync::Int64 { hi: 0, lo: 0 };
+        let cookie = client.connection.send_request(&xcb::sync::Initialize {
+            desired_major_version: 3,
+            desired_minor_version: 1,
+        });
+
+        client.connection.wait_for_reply(cookie)
+            .or_else(|_| Err(Error::PlatformApiFailed("cannot initialize XSync")))?;
+
+        let sync_counter = Self::create_counter(client)?;
+        let extended_sync_counter = Self::create_counter(client)?;
+
+        Ok(Self {
+            sync_counter,
+            sync_value: 0,
+            extended_sync_counter,
+            extended_sync_value: 0,
+            requested_sync_value: None,
+            requested_extended_sync_value: None,
+            pending_frame_value: None,
+            frame_drawn_compositor: None,
+            is_redraw_deferred: false,
+        })
+    }
+
+
+    fn create_counter(client: &ShellClient) -> Result<xcb::sync::Counter> {
+        let counter = client.connection.generate_id();
 
         client.connection.send_and_check_request(&xcb::sync::CreateCounter {
-            id: sync_counter,
-            initial_value: sync_value,
+            id: counter,
+            initial_value: Self::make_sync_int64(0),
         })
         .or_else(|_| Err(Error::PlatformApiFailed("cannot create sync counter")))?;
 
-        Ok(Self { sync_counter, sync_value })
+        Ok(counter)
     }
 
 
+
+    /// The second counter enables the extended synchronisation protocol
     fn init_for_window(&self, client: &ShellClient, window_handle: X11WindowHandle) -> Result<()> {
         use xcb::Xid;
 
@@ -66,7 +150,7 @@ impl X11SysRedrawSubsystem {
             window: window_handle,
             property: client.atoms._NET_WM_SYNC_REQUEST_COUNTER,
             r#type: xcb::x::ATOM_CARDINAL,
-            data: &[self.sync_counter.resource_id()]
+            data: &[self.sync_counter.resource_id(), self.extended_sync_counter.resource_id()]
         })
         .or_else(|_| Err(Error::PlatformApiFailed("cannot init sync counter")))?;
 
@@ -75,43 +159,105 @@ impl X11SysRedrawSubsystem {
 
 
     /// Handles the data received with ClientMessage/_NET_WM_SYNC_REQUEST
-    pub fn set_sync_value(&mut self, value: i64) {
-        self.sync_value.lo = (value & 0xFF_FF_FF_FF) as u32;
-        self.sync_value.hi = ((value >> 32) & 0xFF_FF_FF_FF) as i32;
+    pub fn handle_sync_request(&mut self, data: &[u32; 5]) {
+        let value = (data[2] as i64) | ((data[3] as i32 as i64) << 32);
+
+        if data[4] != 0 {
+            self.requested_extended_sync_value = Some(value);
+        } else {
+            self.requested_sync_value = Some(value);
+        }
     }
 
 
-    fn get_sync_value(&self) -> i64 {
-        (self.sync_value.hi as i64) << 32 + self.sync_value.lo as i64
+    pub fn has_sync_request(&self) -> bool {
+        self.requested_sync_value.is_some() || self.requested_extended_sync_value.is_some()
     }
 
 
-    fn increment_sync_value(&mut self) {
-        self.set_sync_value(self.get_sync_value() + 1);
+    /// Handles ClientMessage/_NET_WM_FRAME_DRAWN. `value` is the extended counter value of the drawn frame,
+    /// `compositor` is the generation of the running compositor.
+    ///
+    /// Returns true if a redraw has been deferred until the frame is drawn.
+    pub fn handle_frame_drawn(&mut self, value: i64, compositor: Option<u32>) -> bool {
+        self.frame_drawn_compositor = compositor;
+
+        // A late report of an older frame does not mean that the last frame is drawn
+        if self.pending_frame_value.is_some_and(|pending_value| value < pending_value) {
+            return false;
+        }
+
+        self.pending_frame_value = None;
+        std::mem::take(&mut self.is_redraw_deferred)
+    }
+
+
+    /// Drawing faster than the compositor displays frames is useless, but only compositors that have reported
+    /// drawn frames before are waited for
+    pub fn should_defer_redraw(&self, compositor: Option<u32>) -> bool {
+        self.pending_frame_value.is_some() && compositor.is_some() && self.frame_drawn_compositor == compositor
     }
 
 
-    fn synchronise(&self, client: &ShellClient) -> Result<()> {
+    fn make_sync_int64(value: i64) -> xcb::sync::Int64 {
+        xcb::sync::Int64 {
+            lo: (value & 0xFF_FF_FF_FF) as u32,
+            hi: (value >> 32) as i32,
+        }
+    }
+
+
+    fn set_counter(client: &ShellClient, counter: xcb::sync::Counter, value: i64) -> Result<()> {
         client.connection.send_and_check_request(&xcb::sync::SetCounter {
-            counter: self.sync_counter,
-            value: self.sync_value
+            counter,
+            value: Self::make_sync_int64(value),
         })
         .or_else(|_| Err(Error::PlatformApiFailed("cannot set sync counter")))?;
         Ok(())
     }
 
 
-    /// Forbids the shell to update the surface on the screen
+    /// Forbids the shell to update the surface on the screen.
+    ///
+    /// Called before the application is asked to draw a frame.
     pub fn lock_surface(&mut self, client: &ShellClient) -> Result<()> {
-        self.increment_sync_value();
-        self.synchronise(client)
+        // An odd value of the extended counter means that a frame is being drawn
+        if self.extended_sync_value % 2 == 0 {
+            self.extended_sync_value += 1;
+            Self::set_counter(client, self.extended_sync_counter, self.extended_sync_value)?;
+        }
+
+        Ok(())
     }
 
 
-    /// Allows the shell to update the surface on teh screen
+    /// Allows the shell to update the surface on the screen.
+    ///
+    /// Called after the application has presented a frame.
     pub fn unlock_surface(&mut self, client: &ShellClient) -> Result<()> {
-        self.increment_sync_value();
-        self.synchronise(client)
+        if let Some(value) = self.requested_sync_value.take() {
+            self.sync_value = value;
+            Self::set_counter(client, self.sync_counter, self.sync_value)?;
+        }
+
+        // The compositor reports only frames that were locked, i.e. drawn while the extended counter was odd
+        let is_frame_completed = self.extended_sync_value % 2 != 0;
+
+        if is_frame_completed {
+            self.extended_sync_value += 1;
+        }
+
+        if let Some(value) = self.requested_extended_sync_value.take() {
+            self.extended_sync_value = self.extended_sync_value.max(value);
+        }
+
+        Self::set_counter(client, self.extended_sync_counter, self.extended_sync_value)?;
+
+        if is_frame_completed {
+            self.pending_frame_value = Some(self.extended_sync_value);
+        }
+
+        Ok(())
     }
 }
 
@@ -122,24 +268,62 @@ impl X11TextInputSubsystem {
     pub fn new(client: &ShellClient, window_handle: X11WindowHandle) -> Result<Self> {
         use xcb::Xid;
 
-        if client.text_input_subsystem.is_none() {
-            return Err(Error::InternalFailure);
-        }
+        let global_subsystem = client.text_input_subsystem.as_ref()
+            .ok_or(Error::InternalFailure)?;
+
+        let input_style = global_subsystem.choose_input_style()?;
+
+        let mut preedit = Box::new(X11PreeditState {
+            window_handle,
+            preedit_windows: global_subsystem.preedit_windows.clone(),
+            text: Vec::new(),
+            caret: 0,
+            is_caret_visible: true,
+            pending_events: Vec::new(),
+        });
 
-        let input_style = ffi::CString::new(xlib::XNInputStyle).unwrap();
+        let input_style_name = ffi::CString::new(xlib::XNInputStyle).unwrap();
         let client_window = ffi::CString::new(xlib::XNClientWindow).unwrap();
         let focus_window = ffi::CString::new(xlib::XNFocusWindow).unwrap();
+        let preedit_attributes = ffi::CString::new(xlib::XNPreeditAttributes).unwrap();
+
+        // Xlib copies the callbacks and the spot location when the input context is created
+        let client_data = preedit.as_mut() as *mut X11PreeditState as xlib::XPointer;
+
+        let callbacks = [
+            xlib::XICCallback { client_data, callback: Some(preedit_start_callback) },
+            xlib::XICCallback { client_data, callback: Some(preedit_done_callback) },
+            xlib::XICCallback { client_data, callback: Some(preedit_draw_callback) },
+            xlib::XICCallback { client_data, callback: Some(preedit_caret_callback) },
+        ];
+
+        let mut spot_location = xlib::XPoint { x: 0, y: 0 };
 
-        let input_method = client.text_input_subsystem.as_ref().unwrap().input_method;
+        let preedit_attribute_list =
+            Self::create_preedit_attribute_list(input_style, &callbacks, &mut spot_location);
 
         let xic = unsafe {
-            xlib::XCreateIC(
-                input_method,
-                input_style.as_ptr(), xlib::XIMPreeditNothing | xlib::XIMStatusNothing,
-                client_window.as_ptr(), window_handle.resource_id(),
-                focus_window.as_ptr(), window_handle.resource_id(),
-                null() as *const u8
-            )
+            if preedit_attribute_list.is_null() {
+                xlib::XCreateIC(
+                    global_subsystem.input_method,
+                    input_style_name.as_ptr(), input_style,
+                    client_window.as_ptr(), window_handle.resource_id() as c_ulong,
+                    focus_window.as_ptr(), window_handle.resource_id() as c_ulong,
+                    null::<u8>()
+                )
+            } else {
+                let xic = xlib::XCreateIC(
+                    global_subsystem.input_method,
+                    input_style_name.as_ptr(), input_style,
+                    client_window.as_ptr(), window_handle.resource_id() as c_ulong,
+                    focus_window.as_ptr(), window_handle.resource_id() as c_ulong,
+                    preedit_attributes.as_ptr(), preedit_attribute_list,
+                    null::<u8>()
+                );
+
+                xlib::XFree(preedit_attribute_list);
+                xic
+            }
         };
 
         if xic.is_null() {
@@ -148,16 +332,509 @@ impl X11TextInputSubsystem {
 
         Ok(X11TextInputSubsystem {
             input_context: xic,
+            input_style,
             input: Vec::with_capacity(16),
-            input_finished: false
+            preedit,
         })
     }
 
 
+    /// Returns null if the input style needs no pre-edit attributes.
+    /// The list points to `callbacks` and `spot_location`, which must outlive it.
+    fn create_preedit_attribute_list(
+        input_style: c_ulong, callbacks: &[xlib::XICCallback; 4], spot_location: &mut xlib::XPoint
+    ) -> *mut ffi::c_void {
+        unsafe {
+            if input_style & xlib::XIMPreeditCallbacks as c_ulong != 0 {
+                xlib::XVaCreateNestedList(0,
+                    xlib::XNPreeditStartCallback_0.as_ptr(), &callbacks[0] as *const xlib::XICCallback,
+                    xlib::XNPreeditDoneCallback_0.as_ptr(), &callbacks[1] as *const xlib::XICCallback,
+                    xlib::XNPreeditDrawCallback_0.as_ptr(), &callbacks[2] as *const xlib::XICCallback,
+                    xlib::XNPreeditCaretCallback_0.as_ptr(), &callbacks[3] as *const xlib::XICCallback,
+                    null::<u8>()
+                )
+            } else if input_style & xlib::XIMPreeditPosition as c_ulong != 0 {
+                xlib::XVaCreateNestedList(0,
+                    xlib::XNSpotLocation_0.as_ptr(), spot_location as *mut xlib::XPoint,
+                    null::<u8>()
+                )
+            } else {
+                null_mut()
+            }
+        }
+    }
+
+
     pub fn destroy(&self) {
         unsafe {
             xlib::XDestroyIC(self.input_context);
         }
     }
 
+
+    /// The spot location is the start of the baseline of the text cursor
+    pub fn set_cursor_area(&self, area: &PixelRect) -> Result<()> {
+        let mut spot_location = xlib::XPoint {
+            x: area.x as i16,
+            y: (area.y + area.height as i32) as i16,
+        };
+
+        let preedit_attributes = ffi::CString::new(xlib::XNPreeditAttributes).unwrap();
+
+        unsafe {
+            let attribute_list = xlib::XVaCreateNestedList(0,
+                xlib::XNSpotLocation_0.as_ptr(), &mut spot_location as *mut xlib::XPoint,
+                null::<u8>()
+            );
+
+            // Input methods that do not use the spot location reject it, which is not an error
+            xlib::XSetICValues(self.input_context, preedit_attributes.as_ptr(), attribute_list, null::<u8>());
+
+            xlib::XFree(attribute_list);
+        }
+
+        Ok(())
+    }
+
+
+    /// The input method processes input only for the focused input context
+    pub fn set_focus(&self, is_focused: bool) {
+        unsafe {
+            if is_focused {
+                xlib::XSetICFocus(self.input_context);
+            } else {
+                xlib::XUnsetICFocus(self.input_context);
+            }
+        }
+    }
+
+
+    pub fn take_preedit_events(&mut self) -> Vec<WindowEvent> {
+        std::mem::take(&mut self.preedit.pending_events)
+    }
+
+
+    /// Returns the text committed by the key press (possibly completing a dead key or compose sequence).
+    /// Control characters are not considered text.
+    pub fn lookup_text(&mut self, client: &ShellClient, event: &xcb::x::KeyPressEvent) -> Option<String> {
+        let mut xkey_event = client.make_xkey_event(event);
+        let mut keysym: xlib::KeySym = 0;
+        let mut status: i32 = 0;
+
+        self.input.resize(self.input.capacity(), 0);
+
+        let length = loop {
+            let length = unsafe {
+                xlib::Xutf8LookupString(
+                    self.input_context,
+                    &mut xkey_event,
+                    self.input.as_mut_ptr() as *mut libc::c_char,
+                    self.input.len() as i32,
+                    &mut keysym,
+                    &mut status
+                )
+            };
+
+            if status != xlib::XBufferOverflow {
+                break length as usize;
+            }
+
+            self.input.resize(length as usize, 0);
+        };
+
+        if status != xlib::XLookupChars && status != xlib::XLookupBoth {
+            return None;
+        }
+
+        let text: String = String::from_utf8_lossy(&self.input[..length])
+            .chars()
+            .filter(|character| !character.is_control())
+            .collect();
+
+        if text.is_empty() {
+            None
+        } else {
+            Some(text)
+        }
+    }
+
+}
+
+
+
+impl X11PreeditState {
+
+    fn push_event(&mut self, event: WindowEvent) {
+        self.pending_events.push(event);
+
+        let mut preedit_windows = self.preedit_windows.borrow_mut();
+
+        if !preedit_windows.contains(&self.window_handle) {
+            preedit_windows.push(self.window_handle);
+        }
+    }
+
+
+    fn push_update_event(&mut self) {
+        let text: String = self.text.iter().collect();
+
+        let cursor_range = if self.is_caret_visible {
+            let caret_offset: usize = self.text[..self.caret.min(self.text.len())].iter()
+                .map(|character| character.len_utf8())
+                .sum();
+
+            Some(caret_offset..caret_offset)
+        } else {
+            None
+        };
+
+        self.push_event(WindowEvent::PreeditUpdate { text, cursor_range });
+    }
+
+}
+
+
+unsafe extern "C" fn preedit_start_callback(
+    _input_context: xlib::XIC, client_data: xlib::XPointer, _call_data: xlib::XPointer
+) -> xlib::Bool {
+    let preedit = &mut *(client_data as *mut X11PreeditState);
+
+    preedit.text.clear();
+    preedit.caret = 0;
+    preedit.push_event(WindowEvent::PreeditStart);
+
+    // The pre-edit text length is not limited
+    -1
+}
+
+
+unsafe extern "C" fn preedit_done_callback(
+    _input_context: xlib::XIC, client_data: xlib::XPointer, _call_data: xlib::XPointer
+) -> xlib::Bool {
+    let preedit = &mut *(client_data as *mut X11PreeditState);
+
+    preedit.text.clear();
+    preedit.caret = 0;
+    preedit.push_event(WindowEvent::PreeditEnd);
+
+    0
+}
+
+
+unsafe extern "C" fn preedit_draw_callback(
+    _input_context: xlib::XIC, client_data: xlib::XPointer, call_data: xlib::XPointer
+) -> xlib::Bool {
+    let preedit = &mut *(client_data as *mut X11PreeditState);
+    let draw = &*(call_data as *const xlib::XIMPreeditDrawCallbackStruct);
+
+    let first = (draw.chg_first.max(0) as usize).min(preedit.text.len());
+    let last = (first + draw.chg_length.max(0) as usize).min(preedit.text.len());
+
+    let new_text: Vec<char> = if draw.text.is_null() || (*draw.text).encoding_is_wchar != 0 {
+        Vec::new()
+    } else {
+        let text = &*draw.text;
+        let multi_byte = text.string.multi_byte;
+
+        if multi_byte.is_null() {
+            Vec::new()
+        } else {
+            ffi::CStr::from_ptr(multi_byte).to_string_lossy().chars().collect()
+        }
+    };
+
+    preedit.text.splice(first..last, new_text);
+    preedit.caret = (draw.caret.max(0) as usize).min(preedit.text.len());
+    preedit.push_update_event();
+
+    0
+}
+
+
+unsafe extern "C" fn preedit_caret_callback(
+    _input_context: xlib::XIC, client_data: xlib::XPointer, call_data: xlib::XPointer
+) -> xlib::Bool {
+    let preedit = &mut *(client_data as *mut X11PreeditState);
+    let caret = &mut *(call_data as *mut xlib::XIMPreeditCaretCallbackStruct);
+
+    let text_length = preedit.text.len();
+
+    preedit.caret = match caret.direction {
+        xlib::XIMCaretDirection::XIMAbsolutePosition => caret.position.max(0) as usize,
+        xlib::XIMCaretDirection::XIMForwardChar => preedit.caret + 1,
+        xlib::XIMCaretDirection::XIMBackwardChar => preedit.caret.saturating_sub(1),
+        xlib::XIMCaretDirection::XIMLineStart => 0,
+        xlib::XIMCaretDirection::XIMLineEnd => text_length,
+        _ => preedit.caret,
+    }.min(text_length);
+
+    preedit.is_caret_visible = caret.style != xlib::XIMCaretStyle::XIMIsInvisible;
+
+    // The input method expects to receive the resulting caret position
+    caret.position = preedit.caret as i32;
+
+    preedit.push_update_event();
+
+    0
+}
+
+
+
+impl X11GlobalSysRedrawSubsystem {
+
+    pub fn new(client: &ShellClient) -> Result<Self> {
+        use xcb::Xid;
+
+        if !client.connection.active_extensions().any(|extension| extension == xcb::Extension::XFixes) {
+            return Err(Error::PlatformApiFailed("XFixes is not supported"));
+        }
+
+        let cookie = client.connection.send_request(&xcb::xfixes::QueryVersion {
+            client_major_version: 1,
+            client_minor_version: 0,
+        });
+
+        client.connection.wait_for_reply(cookie)
+            .or_else(|_| Err(Error::PlatformApiFailed("cannot query XFixes version")))?;
+
+        // Compositing managers own the `_NET_WM_CM_Sn` selection of the screen they manage
+        let selection_name = format!("_NET_WM_CM_S{}", client.default_screen_number);
+
+        let cookie = client.connection.send_request(&xcb::x::InternAtom {
+            only_if_exists: false,
+            name: selection_name.as_bytes(),
+        });
+
+        let compositor_selection = client.connection.wait_for_reply(cookie)
+            .or_else(|_| Err(Error::PlatformApiFailed("cannot get compositor selection atom")))?
+            .atom();
+
+        client.connection.send_and_check_request(&xcb::xfixes::SelectSelectionInput {
+            window: client.get_default_window(),
+            selection: compositor_selection,
+            event_mask: xcb::xfixes::SelectionEventMask::all(),
+        })
+        .or_else(|_| Err(Error::PlatformApiFailed("cannot select compositor selection events")))?;
+
+        let cookie = client.connection.send_request(&xcb::x::GetSelectionOwner { selection: compositor_selection });
+
+        let owner = client.connection.wait_for_reply(cookie)
+            .or_else(|_| Err(Error::PlatformApiFailed("cannot get compositor selection owner")))?
+            .owner();
+
+        Ok(Self {
+            compositor_selection,
+            is_compositor_active: Cell::new(!owner.is_none()),
+            compositor_generation: Cell::new(0),
+            deferred_windows: RefCell::new(Vec::new()),
+        })
+    }
+
+
+    /// Returns the generation of the running compositor, `None` if no compositor is running
+    pub fn get_compositor(&self) -> Option<u32> {
+        self.is_compositor_active.get().then(|| self.compositor_generation.get())
+    }
+
+
+    /// Handles XFixes SelectionNotify.
+    ///
+    /// Returns the windows whose deferred redraws must be delivered, because the new compositor may never
+    /// report drawing their frames.
+    pub fn handle_selection_notify(&self, event: &xcb::xfixes::SelectionNotifyEvent) -> Vec<X11WindowHandle> {
+        use xcb::Xid;
+
+        if event.selection() != self.compositor_selection {
+            return Vec::new();
+        }
+
+        // The owner is none if the compositor has exited
+        self.is_compositor_active.set(!event.owner().is_none());
+        self.compositor_generation.set(self.compositor_generation.get().wrapping_add(1));
+
+        self.deferred_windows.take()
+    }
+
+}
+
+
+impl X11GlobalXInputSubsystem {
+
+    pub fn new(client: &ShellClient) -> Result<Self> {
+        if !client.connection.active_extensions().any(|extension| extension == xcb::Extension::Input) {
+            return Err(Error::PlatformApiFailed("XInput is not supported"));
+        }
+
+        let cookie = client.connection.send_request(&xcb::xinput::XiQueryVersion {
+            major_version: 2,
+            minor_version: 1,
+        });
+
+        let reply = client.connection.wait_for_reply(cookie)
+            .or_else(|_| Err(Error::PlatformApiFailed("cannot query XInput version")))?;
+
+        if (reply.major_version(), reply.minor_version()) < (2, 1) {
+            return Err(Error::PlatformApiFailed("XInput 2.1 is not supported"));
+        }
+
+        Ok(Self {
+            scroll_state: RefCell::new(X11ScrollState::default()),
+        })
+    }
+
+
+    /// Replaces core pointer button and motion events of the window with their XInput versions
+    pub fn init_for_window(&self, client: &ShellClient, window_handle: X11WindowHandle) -> Result<()> {
+        use xcb::xinput::XiEventMask;
+
+        let mask = XiEventMask::BUTTON_PRESS
+            | XiEventMask::BUTTON_RELEASE
+            | XiEventMask::MOTION
+            | XiEventMask::DEVICE_CHANGED;
+
+        client.connection.send_and_check_request(&xcb::xinput::XiSelectEvents {
+            window: window_handle,
+            masks: &[xcb::xinput::EventMaskBuf::new(xcb::xinput::Device::AllMaster, &[mask])],
+        })
+        .or_else(|_| Err(Error::PlatformApiFailed("cannot select XInput events")))?;
+
+        Ok(())
+    }
+
+
+    /// Stops receiving XInput events of a window that is not destroyed by the library
+    pub fn deinit_for_window(&self, client: &ShellClient, window_handle: X11WindowHandle) -> Result<()> {
+        use xcb::xinput::XiEventMask;
+
+        client.connection.send_and_check_request(&xcb::xinput::XiSelectEvents {
+            window: window_handle,
+            masks: &[xcb::xinput::EventMaskBuf::new(xcb::xinput::Device::AllMaster, &[XiEventMask::empty()])],
+        })
+        .or_else(|_| Err(Error::PlatformApiFailed("cannot deselect XInput events")))?;
+
+        Ok(())
+    }
+
+
+    /// Must be called when the pointer enters a window or the device changes,
+    /// otherwise the next scroll delta is computed from an outdated value
+    pub fn reset_scroll_values(&self) {
+        let mut scroll_state = self.scroll_state.borrow_mut();
+        scroll_state.last_values.clear();
+        scroll_state.unreported_steps_x = 0.0;
+        scroll_state.unreported_steps_y = 0.0;
+    }
+
+
+    pub fn forget_device(&self, device_id: u16) {
+        self.scroll_state.borrow_mut().devices.remove(&device_id);
+        self.reset_scroll_values();
+    }
+
+
+    /// Returns `None` if the event does not change any scroll valuators
+    pub fn get_scroll_delta(&self, client: &ShellClient, event: &xcb::xinput::MotionEvent) -> Option<ScrollDelta> {
+        let device_id = event.source().id();
+        let mut scroll_state = self.scroll_state.borrow_mut();
+
+        scroll_state.devices.entry(device_id)
+            .or_insert_with(|| Self::query_scroll_valuators(client, device_id).unwrap_or_default());
+
+        let (mut delta_x, mut delta_y) = (0.0, 0.0);
+        let mut has_scrolled = false;
+
+        for (number, value) in Self::get_valuator_values(event) {
+            let valuator = scroll_state.devices[&device_id].iter()
+                .find(|valuator| valuator.number == number)
+                .map(|valuator| (valuator.is_horizontal, valuator.increment));
+
+            let (is_horizontal, increment) = match valuator {
+                Some(valuator) => valuator,
+                None => continue,
+            };
+
+            if let Some(last_value) = scroll_state.last_values.insert((device_id, number), value) {
+                let delta = (value - last_value) / increment;
+                has_scrolled = true;
+
+                if is_horizontal {
+                    delta_x += delta;
+                } else {
+                    delta_y += delta;
+                }
+            }
+        }
+
+        if !has_scrolled {
+            return None;
+        }
+
+        scroll_state.unreported_steps_x += delta_x;
+        scroll_state.unreported_steps_y += delta_y;
+
+        let steps_x = scroll_state.unreported_steps_x.trunc();
+        let steps_y = scroll_state.unreported_steps_y.trunc();
+
+        scroll_state.unreported_steps_x -= steps_x;
+        scroll_state.unreported_steps_y -= steps_y;
+
+        Some(ScrollDelta {
+            steps_x: steps_x as i32,
+            steps_y: steps_y as i32,
+            x: delta_x,
+            y: delta_y,
+            unit: ScrollUnit::Lines,
+        })
+    }
+
+
+    /// Returns true if the event changes the pointer position, not only other valuators
+    pub fn has_pointer_moved(event: &xcb::xinput::MotionEvent) -> bool {
+        Self::get_valuator_values(event).any(|(number, _)| number <= 1)
+    }
+
+
+    /// Iterates over the numbers and values of the valuators present in the event
+    fn get_valuator_values(event: &xcb::xinput::MotionEvent) -> impl Iterator<Item = (u16, f64)> + '_ {
+        let valuator_numbers = event.valuator_mask().iter()
+            .enumerate()
+            .flat_map(|(word_index, word)| {
+                (0..32).filter(move |bit| word & (1 << bit) != 0).map(move |bit| (word_index * 32 + bit) as u16)
+            });
+
+        valuator_numbers.zip(event.axisvalues().iter().map(fp3232_to_f64))
+    }
+
+
+    fn query_scroll_valuators(client: &ShellClient, device_id: u16) -> Result<Vec<X11ScrollValuator>> {
+        let cookie = client.connection.send_request(&xcb::xinput::XiQueryDevice {
+            device: xcb::xinput::Device::Id(device_id),
+        });
+
+        let reply = client.connection.wait_for_reply(cookie)
+            .or_else(|_| Err(Error::PlatformApiFailed("cannot query XInput device")))?;
+
+        let valuators = reply.infos()
+            .flat_map(|info| info.classes().map(|class| class.data()).collect::<Vec<_>>())
+            .filter_map(|class_data| match class_data {
+                xcb::xinput::DeviceClassData::Scroll { number, scroll_type, increment, .. } => {
+                    Some(X11ScrollValuator {
+                        number,
+                        is_horizontal: scroll_type == xcb::xinput::ScrollType::Horizontal,
+                        increment: fp3232_to_f64(&increment),
+                    })
+                }
+                _ => None,
+            })
+            .filter(|valuator| valuator.increment != 0.0)
+            .collect();
+
+        Ok(valuators)
+    }
+
+}
+
+
+fn fp3232_to_f64(value: &xcb::xinput::Fp3232) -> f64 {
+    value.integral as f64 + value.frac as f64 / (1u64 << 32) as f64
 }
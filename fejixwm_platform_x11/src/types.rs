@@ -3,8 +3,10 @@
 pub(crate) use crate::core::{
     errors::*,
     events::{
-        Event, ShellMessageTrait, MessageCallback, EventHandler, ListeningBehavior, ListeningSettings,
+        Event, WindowEvent, ShellMessageTrait, MessageCallback, EventHandler, ListeningBehavior, ListeningSettings,
     },
+    keyboard::*,
+    mouse::*,
     *
 };
 
@@ -13,6 +15,9 @@ pub(crate) use xcb;
 
 pub(crate) use std::{
     any::Any,
+    rc::Rc,
+    cell::{Cell, RefCell},
+    collections::HashMap,
     ptr::{null, null_mut},
     ffi,
 };
@@ -39,13 +44,58 @@ pub struct ShellClient {
     pub(crate) fake_window_handle: X11WindowHandle,
 
     pub(crate) text_input_subsystem: Option<X11GlobalTextInputSubsystem>,
+
+    /// Present if the server supports XInput 2.1 and smooth scrolling
+    pub(crate) xinput_subsystem: Option<X11GlobalXInputSubsystem>,
+
+    /// Present if the server supports XFixes, which reports compositing managers starting and exiting
+    pub(crate) sys_redraw_subsystem: Option<X11GlobalSysRedrawSubsystem>,
+
+    /// Time of the last key or button press. Shells use it to decide whether focus requests are legitimate.
+    pub(crate) last_user_time: Cell<xcb::x::Timestamp>,
+
+    /// Windows that have requested a redraw during the current loop iteration
+    pub(crate) redraw_windows: RefCell<Vec<X11WindowHandle>>,
+
+    /// Foreign windows embedded via XEmbed, mapped to their embedders.
+    /// Events of the embedded windows are delivered to the embedders.
+    pub(crate) xembed_clients: RefCell<HashMap<X11WindowHandle, X11WindowHandle>>,
 }
 
 pub struct Window {
     pub(crate) handle: X11WindowHandle,
     pub(crate) state: X11WindowState,
     pub(crate) text_input: Option<X11TextInputSubsystem>,
-    pub(crate) sys_redraw: Option<X11SysRedrawSubsystem>
+    pub(crate) sys_redraw: Option<X11SysRedrawSubsystem>,
+
+    pub(crate) visualid: xcb::x::Visualid,
+
+    /// The window was created by another party and is not destroyed by the library
+    pub(crate) is_foreign: bool,
+
+    /// The window was created inside a window of the program's host rather than the root window.
+    /// Its position is kept relative to the parent, which receives no events when the host moves it.
+    pub(crate) is_child: bool,
+
+    /// Present if the window hosts foreign windows via XEmbed
+    pub(crate) xembed_embedder: Option<X11XEmbedEmbedder>,
+
+    /// Present if the window can be embedded into foreign windows via XEmbed
+    pub(crate) xembed_client: Option<X11XEmbedClient>,
+
+    /// Written to WM_NORMAL_HINTS
+    pub(crate) size_constraints: SizeConstraints,
+    pub(crate) is_resizable: bool,
+
+    /// Size requested by the program that has not been reported by ConfigureNotify yet.
+    /// Non-resizable windows are fixed to this size rather than the outdated cached size.
+    pub(crate) pending_size: Option<PixelSize>,
+
+    /// Set once the program has chosen the position, asks the window manager to keep it
+    pub(crate) requested_position: Option<PixelPosition>,
+
+    /// Colormap created for the window if its visual differs from the root visual
+    pub(crate) colormap: Option<xcb::x::Colormap>,
 }
 
 
@@ -63,11 +113,20 @@ xcb::atoms_struct! {
         pub UTF8_STRING => b"UTF8_STRING",
         
         pub _NET_WM_NAME => b"_NET_WM_NAME",
+        pub _NET_ACTIVE_WINDOW => b"_NET_ACTIVE_WINDOW",
+        pub _NET_FRAME_EXTENTS => b"_NET_FRAME_EXTENTS",
         pub _NET_WM_PING => b"_NET_WM_PING",
         pub _NET_WM_SYNC_REQUEST => b"_NET_WM_SYNC_REQUEST",
         pub _NET_WM_SYNC_REQUEST_COUNTER => b"_NET_WM_SYNC_REQUEST_COUNTER",
+        pub _NET_WM_FRAME_DRAWN => b"_NET_WM_FRAME_DRAWN",
+        pub _NET_WM_FRAME_TIMINGS => b"_NET_WM_FRAME_TIMINGS",
+
+        pub _XEMBED => b"_XEMBED" only_if_exists = false,
+        pub _XEMBED_INFO => b"_XEMBED_INFO" only_if_exists = false,
 
         pub FEJIXWM_USER_EVENT => b"FEJIXWM_USER_EVENT" only_if_exists = false,
+        pub FEJIXWM_TEXT_INPUT_EVENT => b"FEJIXWM_TEXT_INPUT_EVENT" only_if_exists = false,
+        pub FEJIXWM_REDRAW_EVENT => b"FEJIXWM_REDRAW_EVENT" only_if_exists = false,
     }
 }
 
@@ -75,26 +134,144 @@ xcb::atoms_struct! {
 
 pub(crate) struct X11WindowState {
     pub size: PixelSize,
+
+    /// Relative to the root window, or to the parent window for child windows
+    pub position: PixelPosition,
+
+    /// Relative to the parent window, which is often the window manager's frame
+    pub parent_position: PixelPosition,
+
+    pub frame_extents: X11FrameExtents,
+
+    /// Indexed by X11 keycodes. Used to detect auto-repeated key presses.
+    pub pressed_keys: [bool; 256],
+
+    /// Areas collected from a series of Expose events that has not ended yet
+    pub damage: Vec<PixelRect>,
+}
+
+/// Sizes of the window decorations drawn by the window manager
+#[derive(Clone, Default)]
+pub(crate) struct X11FrameExtents {
+    pub left: u32,
+    pub right: u32,
+    pub top: u32,
+    pub bottom: u32,
 }
 
 pub(crate) struct X11WindowVisualInfo {
     pub visualid: xcb::x::Visualid,
-    pub colormap: xcb::x::Colormap,
+    /// `None` if the window needs its own colormap
+    pub colormap: Option<xcb::x::Colormap>,
     pub color_depth: u8,
 }
 
 
 pub(crate) struct X11SysRedrawSubsystem {
+    /// Set to the value of the last basic sync request when the next frame is presented
     pub sync_counter: xcb::sync::Counter,
-    pub sync_value: xcb::sync::Int64,
+    pub sync_value: i64,
+
+    /// Odd while a frame is being drawn, even otherwise
+    pub extended_sync_counter: xcb::sync::Counter,
+    pub extended_sync_value: i64,
+
+    /// Values received with _NET_WM_SYNC_REQUEST that wait for the next presented frame
+    pub requested_sync_value: Option<i64>,
+    pub requested_extended_sync_value: Option<i64>,
+
+    /// Value of the extended counter after the last presented frame that the compositor has not reported drawing
+    pub pending_frame_value: Option<i64>,
+
+    /// Generation of the compositor that has reported drawing a frame of this window.
+    /// Not all compositors report drawn frames, so redraws are deferred only for compositors known to do it.
+    pub frame_drawn_compositor: Option<u32>,
+
+    /// True if a redraw requested by the application waits for the pending frame to be drawn
+    pub is_redraw_deferred: bool,
+}
+
+pub(crate) struct X11XEmbedEmbedder {
+    /// Embedded foreign window
+    pub client: Option<X11WindowHandle>,
+
+    /// Flags from the last known _XEMBED_INFO of the client
+    pub client_flags: u32,
+
+    pub is_focused: bool,
+}
+
+pub(crate) struct X11XEmbedClient {
+    /// Window that embeds this window
+    pub embedder: Option<X11WindowHandle>,
+
+    /// Flags published in _XEMBED_INFO
+    pub flags: u32,
 }
 
 pub(crate) struct X11GlobalTextInputSubsystem {
     pub input_method: xlib::XIM,
+
+    /// Windows that have pre-edit events waiting to be delivered.
+    /// Shared with the pre-edit callbacks of all input contexts.
+    pub preedit_windows: Rc<RefCell<Vec<X11WindowHandle>>>,
+}
+
+pub(crate) struct X11GlobalSysRedrawSubsystem {
+    /// `_NET_WM_CM_Sn` of the default screen, owned by the running compositing manager
+    pub compositor_selection: xcb::x::Atom,
+
+    pub is_compositor_active: Cell<bool>,
+
+    /// Incremented whenever the owner of the compositor selection changes
+    pub compositor_generation: Cell<u32>,
+
+    /// Windows with redraws deferred until the compositor draws their frames
+    pub deferred_windows: RefCell<Vec<X11WindowHandle>>,
+}
+
+pub(crate) struct X11GlobalXInputSubsystem {
+    pub scroll_state: RefCell<X11ScrollState>,
+}
+
+#[derive(Default)]
+pub(crate) struct X11ScrollState {
+    /// Scroll valuators of slave devices indexed by device IDs
+    pub devices: HashMap<u16, Vec<X11ScrollValuator>>,
+
+    /// Last known values of scroll valuators indexed by device IDs and valuator numbers.
+    /// XInput reports absolute values, so the first value after entering a window only sets the starting point.
+    pub last_values: HashMap<(u16, u16), f64>,
+
+    /// Fractional wheel steps that have not been reported yet
+    pub unreported_steps_x: f64,
+    pub unreported_steps_y: f64,
+}
+
+pub(crate) struct X11ScrollValuator {
+    pub number: u16,
+    pub is_horizontal: bool,
+
+    /// The valuator change that corresponds to one wheel step
+    pub increment: f64,
 }
 
 pub(crate) struct X11TextInputSubsystem {
     pub input_context: xlib::XIC,
+    pub input_style: std::os::raw::c_ulong,
     pub input: Vec<u8>,
-    pub input_finished: bool,
+
+    /// Modified by the input method callbacks, so the address must not change
+    pub preedit: Box<X11PreeditState>,
+}
+
+pub(crate) struct X11PreeditState {
+    pub window_handle: X11WindowHandle,
+    pub preedit_windows: Rc<RefCell<Vec<X11WindowHandle>>>,
+
+    pub text: Vec<char>,
+    pub caret: usize,
+    pub is_caret_visible: bool,
+
+    pub pending_events: Vec<WindowEvent>,
 }
\ No newline at end of file